@@ -0,0 +1,61 @@
+use core::fmt;
+
+/// The kind of an autosquash commit, named after the prefix `git` gives its message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Autosquash {
+    Fixup,
+    Squash,
+    Amend,
+}
+impl Autosquash {
+    const ALL: [Autosquash; 3] = [Autosquash::Fixup, Autosquash::Squash, Autosquash::Amend];
+
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Autosquash::Fixup => "fixup! ",
+            Autosquash::Squash => "squash! ",
+            Autosquash::Amend => "amend! ",
+        }
+    }
+}
+impl fmt::Display for Autosquash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.prefix().trim_end_matches("! "))
+    }
+}
+
+/// Splits the autosquash prefixes off a commit message.
+///
+/// Returns the kind of the outermost prefix, if any, and the wrapped message, which is what
+/// should be validated. Prefixes may be repeated, e.g. `fixup! fixup! feat: add x` when fixing
+/// up a fixup commit.
+///
+/// # Examples
+///
+/// ```rust
+/// # use cc_scanner::autosquash::{split_autosquash, Autosquash};
+/// let (kind, message) = split_autosquash("fixup! feat(cli): add flags");
+/// assert_eq!(kind, Some(Autosquash::Fixup));
+/// assert_eq!(message, "feat(cli): add flags");
+///
+/// assert_eq!(split_autosquash("feat: add x"), (None, "feat: add x"));
+/// ```
+pub fn split_autosquash(message: &str) -> (Option<Autosquash>, &str) {
+    let mut kind = None;
+    let mut rest = message;
+
+    while let Some((found, stripped)) = Autosquash::ALL
+        .iter()
+        .find_map(|kind| rest.strip_prefix(kind.prefix()).map(|rest| (*kind, rest)))
+    {
+        kind.get_or_insert(found);
+        rest = stripped;
+    }
+
+    (kind, rest)
+}
@@ -5,6 +5,11 @@ use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 #[derive(Debug, Clone, Eq, PartialEq, EnumIter)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "String", from = "String")
+)]
 pub enum CommitType {
     Feature,
     Bug,
@@ -26,7 +31,7 @@ impl From<&str> for CommitType {
             "fix" => CommitType::Bug, // -> TODO(@1): Brittle
             "chore" => CommitType::Chore,
             "revert" => CommitType::Revert,
-            "per" => CommitType::Perf,
+            "perf" => CommitType::Perf,
             "doc" => CommitType::Doc,
             "style" => CommitType::Style,
             "refactor" => CommitType::Refactor,
@@ -37,6 +42,16 @@ impl From<&str> for CommitType {
         }
     }
 }
+impl From<String> for CommitType {
+    fn from(commit_type: String) -> Self {
+        CommitType::from(commit_type.as_str())
+    }
+}
+impl From<CommitType> for String {
+    fn from(commit_type: CommitType) -> Self {
+        commit_type.to_string()
+    }
+}
 impl fmt::Display for CommitType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let commit_type_str = match self {
@@ -79,6 +94,11 @@ impl CommitType {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Separator {
     #[default]
     Colon,
@@ -107,6 +127,7 @@ impl fmt::Display for Separator {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Footer {
     pub token: String,
     pub separator: Separator,
@@ -132,6 +153,7 @@ impl From<Pair<'_, Rule>> for Footer {
     }
 }
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scope {
     pub noun: String,
 }
@@ -139,6 +161,7 @@ pub struct Scope {
 pub struct Description {}
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConventionalCommit {
     pub commit_type: CommitType,
     pub scope: Option<Scope>,
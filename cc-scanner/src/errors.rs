@@ -1,5 +1,5 @@
 use core::fmt;
-use pest::error::Error as PestError;
+use pest::error::{Error as PestError, LineColLocation};
 use thiserror::Error;
 
 use crate::parser::Rule;
@@ -11,6 +11,7 @@ pub struct ParseError {
 }
 
 #[derive(Debug, PartialEq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum ParseErrorKind {
     #[error("Invalid commit type. Commit type should be ASCII_ALPHA.")]
@@ -62,6 +63,20 @@ impl From<PestError<Rule>> for ParseError {
         }
     }
 }
+impl ParseError {
+    /// Returns the 1-based line and column at which parsing failed.
+    pub fn line_col(&self) -> (usize, usize) {
+        match self.inner.line_col {
+            LineColLocation::Pos(pos) => pos,
+            LineColLocation::Span(start, _) => start,
+        }
+    }
+
+    /// Returns the parser's description of what it expected to find, e.g. "expected scope".
+    pub fn detail(&self) -> String {
+        self.inner.variant.message().to_string()
+    }
+}
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}: {}", self.kind, self.inner)
@@ -0,0 +1,97 @@
+use core::fmt;
+
+/// Headers `git`, GitHub and GitLab give the merge commits they create.
+const MERGE_PREFIXES: [&str; 7] = [
+    "Merge branch ",
+    "Merge branches ",
+    "Merge remote-tracking branch ",
+    "Merge tag ",
+    "Merge commit ",
+    "Merge pull request #",
+    "Merge request ",
+];
+
+/// The line `git revert` adds to the body of the messages it generates.
+const REVERTS_COMMIT: &str = "This reverts commit ";
+
+/// What produced a commit message, as far as can be told from the message alone.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CommitKind {
+    /// Anything not generated by `git` or a forge, which should be a conventional commit.
+    Conventional,
+    /// A message such as `Merge branch 'x' into main` or `Merge pull request #12 from ...`.
+    Merge,
+    /// A message such as `Revert "feat: add x"`, as generated by `git revert`.
+    Revert,
+}
+impl fmt::Display for CommitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            CommitKind::Conventional => "conventional",
+            CommitKind::Merge => "merge",
+            CommitKind::Revert => "revert",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+/// Classifies a commit message by its header.
+///
+/// Only the messages `git` and forges generate are recognised; a conventional `revert: ...`
+/// commit is [`CommitKind::Conventional`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use cc_scanner::kind::{classify, CommitKind};
+/// assert_eq!(classify("Merge branch 'topic' into main"), CommitKind::Merge);
+/// assert_eq!(classify("Revert \"feat: add x\""), CommitKind::Revert);
+/// assert_eq!(classify("revert: feat: add x"), CommitKind::Conventional);
+/// ```
+pub fn classify(message: &str) -> CommitKind {
+    let header = message.lines().next().unwrap_or_default().trim_end();
+
+    if MERGE_PREFIXES
+        .iter()
+        .any(|prefix| header.starts_with(prefix))
+    {
+        return CommitKind::Merge;
+    }
+
+    // `git revert` of a revert produces `Reapply "..."` since git 2.43
+    let quoted = header
+        .strip_prefix("Revert \"")
+        .or_else(|| header.strip_prefix("Reapply \""));
+
+    if quoted.is_some_and(|rest| rest.ends_with('"')) {
+        return CommitKind::Revert;
+    }
+
+    CommitKind::Conventional
+}
+
+/// Extracts the SHA from the `This reverts commit <sha>.` line `git revert` adds to the body.
+///
+/// # Examples
+///
+/// ```rust
+/// # use cc_scanner::kind::reverted_commit;
+/// let message = "Revert \"feat: add x\"\n\nThis reverts commit 1a2b3c4d.";
+/// assert_eq!(reverted_commit(message), Some("1a2b3c4d"));
+/// ```
+pub fn reverted_commit(message: &str) -> Option<&str> {
+    message.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix(REVERTS_COMMIT)?;
+        let end = rest
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(rest.len());
+        let sha = &rest[..end];
+
+        (4..=40).contains(&sha.len()).then_some(sha)
+    })
+}
@@ -2,9 +2,13 @@ use conventional_commit::{CommitType, ConventionalCommit, Footer, Scope};
 use errors::ParseError;
 use parser::{CCScanner, Rule};
 use pest::Parser;
+use spans::{CommitSpans, FooterSpans, SpannedCommit};
+pub mod autosquash;
 pub mod conventional_commit;
 pub mod errors;
+pub mod kind;
 mod parser;
+pub mod spans;
 
 /// Parses the commit type (e.g., "feat", "fix", "docs", etc.) from the provided subject string.
 ///
@@ -22,8 +26,12 @@ mod parser;
 /// # Examples
 ///
 /// ```rust
+/// # use cc_scanner::{conventional_commit::CommitType, parse_commit_type};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let commit_type = parse_commit_type("feat: add a new feature")?;
-/// assert_eq!(commit_type, CommitType::Feat);
+/// assert_eq!(commit_type, CommitType::Feature);
+/// # Ok(())
+/// # }
 /// ```
 pub fn parse_commit_type(subject: &str) -> Result<CommitType, Box<ParseError>> {
     match CCScanner::parse(Rule::commit_type, subject) {
@@ -53,8 +61,12 @@ pub fn parse_commit_type(subject: &str) -> Result<CommitType, Box<ParseError>> {
 /// # Examples
 ///
 /// ```rust
+/// # use cc_scanner::parse_scope;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let scope = parse_scope("cli")?;
 /// assert_eq!(scope.noun, "cli");
+/// # Ok(())
+/// # }
 /// ```
 pub fn parse_scope(scope: &str) -> Result<Scope, ParseError> {
     match CCScanner::parse(Rule::scope_token, scope) {
@@ -85,8 +97,12 @@ pub fn parse_scope(scope: &str) -> Result<Scope, ParseError> {
 /// # Examples
 ///
 /// ```rust
+/// # use cc_scanner::parse_description;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let description = parse_description("feat(scope): add something new")?;
 /// // Inspect `description` as needed
+/// # Ok(())
+/// # }
 /// ```
 pub fn parse_description(subject: &str) -> Result<String, ParseError> {
     match CCScanner::parse(Rule::description, subject) {
@@ -115,8 +131,12 @@ pub fn parse_description(subject: &str) -> Result<String, ParseError> {
 /// # Examples
 ///
 /// ```rust
+/// # use cc_scanner::parse_body;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let body = parse_body("This is the body of the commit")?;
 /// // Inspect `body` as needed
+/// # Ok(())
+/// # }
 /// ```
 pub fn parse_body(subject: &str) -> Result<String, ParseError> {
     match CCScanner::parse(Rule::body, subject) {
@@ -145,8 +165,12 @@ pub fn parse_body(subject: &str) -> Result<String, ParseError> {
 /// # Examples
 ///
 /// ```rust
+/// # use cc_scanner::parse_footer;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let footer = parse_footer("Signed-off-by: Some One <some@one.com>")?;
 /// // Inspect `footer` as needed
+/// # Ok(())
+/// # }
 /// ```
 pub fn parse_footer(subject: &str) -> Result<Footer, ParseError> {
     match CCScanner::parse(Rule::footer, subject) {
@@ -177,9 +201,13 @@ pub fn parse_footer(subject: &str) -> Result<Footer, ParseError> {
 /// # Examples
 ///
 /// ```rust
+/// # use cc_scanner::parse_footers;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let footers_str = "Signed-off-by: Some One <some@one.com>\nCo-authored-by: Another <another@some.com>";
 /// let footers = parse_footers(footers_str)?;
 /// assert_eq!(footers.len(), 2);
+/// # Ok(())
+/// # }
 /// ```
 pub fn parse_footers(footer: &str) -> Result<Vec<Footer>, ParseError> {
     match CCScanner::parse(Rule::footers, footer) {
@@ -222,8 +250,9 @@ pub fn parse_footers(footer: &str) -> Result<Vec<Footer>, ParseError> {
 /// # Examples
 ///
 /// ```rust
-/// let commit_message = r#"
-/// feat(cli): add a new command
+/// # use cc_scanner::parse_commit;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let commit_message = r#"feat(cli): add a new command
 ///
 /// This introduces a new subcommand called 'serve' that
 /// handles local development setup.
@@ -233,9 +262,40 @@ pub fn parse_footers(footer: &str) -> Result<Vec<Footer>, ParseError> {
 ///
 /// let conventional_commit = parse_commit(commit_message)?;
 /// // Inspect `conventional_commit` as needed
+/// # Ok(())
+/// # }
 /// ```
 pub fn parse_commit(commit_str: &str) -> Result<ConventionalCommit, ParseError> {
+    parse_commit_with_spans(commit_str).map(|spanned| spanned.commit)
+}
+
+/// Parses a full conventional commit message, also returning where each component was found.
+///
+/// This behaves exactly like [`parse_commit`], but additionally records the byte range of the
+/// commit type, scope, description, body and each footer's token and value, so callers can
+/// point at the exact text responsible for a problem.
+///
+/// # Errors
+///
+/// Returns a `ParseError` if the commit message fails to parse according to the
+/// conventional commit grammar.
+///
+/// # Examples
+///
+/// ```rust
+/// # use cc_scanner::parse_commit_with_spans;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let message = "feat(cli): add a new command";
+/// let spanned = parse_commit_with_spans(message)?;
+///
+/// assert_eq!(&message[spanned.spans.commit_type], "feat");
+/// assert_eq!(&message[spanned.spans.scope.unwrap()], "cli");
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_commit_with_spans(commit_str: &str) -> Result<SpannedCommit, ParseError> {
     let mut commit = ConventionalCommit::default();
+    let mut spans = CommitSpans::default();
 
     match CCScanner::parse(Rule::conventional_commit, commit_str) {
         Ok(mut rules) => {
@@ -243,10 +303,20 @@ pub fn parse_commit(commit_str: &str) -> Result<ConventionalCommit, ParseError>
 
             for pair in pairs.into_inner() {
                 match pair.as_rule() {
-                    Rule::header => commit.set_header(pair),
-                    Rule::body => commit.set_body(String::from(pair.as_str())),
+                    Rule::header => {
+                        spans.set_header(&pair);
+                        commit.set_header(pair);
+                    }
+                    Rule::body => {
+                        let span = pair.as_span();
+
+                        spans.body = Some(span.start()..span.end());
+                        commit.set_body(String::from(pair.as_str()));
+                    }
                     Rule::footers => {
                         for inner_pair in pair.into_inner() {
+                            spans.footers.push(FooterSpans::from(&inner_pair));
+
                             let footer = Footer::from(inner_pair);
 
                             commit.set_footer(footer);
@@ -256,7 +326,7 @@ pub fn parse_commit(commit_str: &str) -> Result<ConventionalCommit, ParseError>
                 }
             }
 
-            Ok(commit)
+            Ok(SpannedCommit { commit, spans })
         }
         Err(pest_error) => Err(ParseError::from(pest_error)),
     }
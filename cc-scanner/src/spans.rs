@@ -0,0 +1,90 @@
+use crate::{conventional_commit::ConventionalCommit, parser::Rule};
+use pest::iterators::Pair;
+use std::ops::Range;
+
+/// A byte range into the parsed commit message.
+pub type Span = Range<usize>;
+
+/// Byte ranges of the parts of a single footer.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FooterSpans {
+    pub token: Span,
+    pub separator: Span,
+    /// Range of the footer value, excluding surrounding whitespace.
+    pub content: Span,
+}
+impl From<&Pair<'_, Rule>> for FooterSpans {
+    fn from(pair: &Pair<'_, Rule>) -> Self {
+        let mut spans = FooterSpans::default();
+
+        for inner_pair in pair.clone().into_inner() {
+            match inner_pair.as_rule() {
+                Rule::footer_token => spans.token = span(&inner_pair),
+                Rule::footer_token_separator => spans.separator = span(&inner_pair),
+                Rule::footer_content => spans.content = trimmed_span(&inner_pair),
+                _ => {}
+            }
+        }
+
+        spans
+    }
+}
+
+/// Byte ranges of every component of a parsed commit message.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommitSpans {
+    pub header: Span,
+    pub commit_type: Span,
+    /// Range of the scope noun, excluding the parentheses.
+    pub scope: Option<Span>,
+    pub breaking_change_indicator: Option<Span>,
+    pub description: Span,
+    pub body: Option<Span>,
+    pub footers: Vec<FooterSpans>,
+}
+impl CommitSpans {
+    pub(crate) fn set_header(&mut self, pair: &Pair<'_, Rule>) {
+        self.header = span(pair);
+
+        for inner_pair in pair.clone().into_inner() {
+            match inner_pair.as_rule() {
+                Rule::commit_type => self.commit_type = span(&inner_pair),
+                Rule::description => self.description = span(&inner_pair),
+                Rule::scope => {
+                    for scope_rule_pair in inner_pair.into_inner() {
+                        if scope_rule_pair.as_rule() == Rule::scope_token {
+                            self.scope = Some(span(&scope_rule_pair));
+                        }
+                    }
+                }
+                Rule::breaking_change_indicator => {
+                    self.breaking_change_indicator = Some(span(&inner_pair));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// A parsed commit alongside the location of each of its components in the source message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpannedCommit {
+    pub commit: ConventionalCommit,
+    pub spans: CommitSpans,
+}
+
+fn span(pair: &Pair<'_, Rule>) -> Span {
+    let span = pair.as_span();
+
+    span.start()..span.end()
+}
+
+fn trimmed_span(pair: &Pair<'_, Rule>) -> Span {
+    let text = pair.as_str();
+    let start = pair.as_span().start() + (text.len() - text.trim_start().len());
+
+    start..start + text.trim().len()
+}
@@ -0,0 +1,34 @@
+use cc_scanner::{
+    autosquash::{split_autosquash, Autosquash},
+    parse_commit,
+};
+use indoc::indoc;
+
+#[test]
+fn test_repeated_prefixes() {
+    let (kind, message) = split_autosquash("fixup! squash! fix: handle eof");
+
+    assert_eq!(kind, Some(Autosquash::Fixup));
+    assert_eq!(message, "fix: handle eof");
+}
+
+#[test]
+fn test_squash_message_is_validated_with_its_body() {
+    let commit = indoc! {"
+        squash! feat: add flags
+
+        Also document them."
+    };
+
+    let (kind, message) = split_autosquash(commit);
+    let parsed = parse_commit(message).unwrap();
+
+    assert_eq!(kind, Some(Autosquash::Squash));
+    assert_eq!(parsed.description, "add flags");
+    assert_eq!(parsed.body.as_deref(), Some("Also document them."));
+}
+
+#[test]
+fn test_prefix_must_be_followed_by_a_space() {
+    assert_eq!(split_autosquash("fixup!: x"), (None, "fixup!: x"));
+}
@@ -0,0 +1,44 @@
+use cc_scanner::kind::{classify, reverted_commit, CommitKind};
+use indoc::indoc;
+
+#[test]
+fn test_merge_messages() {
+    assert_eq!(classify("Merge branch 'topic'"), CommitKind::Merge);
+    assert_eq!(
+        classify("Merge remote-tracking branch 'origin/main' into main"),
+        CommitKind::Merge
+    );
+    assert_eq!(
+        classify("Merge pull request #12 from owner/topic\n\nfeat: add x"),
+        CommitKind::Merge
+    );
+    assert_eq!(classify("Merge things"), CommitKind::Conventional);
+}
+
+#[test]
+fn test_revert_message() {
+    let commit = indoc! {r#"
+        Revert "feat(cli): add flags"
+
+        This reverts commit 0123456789abcdef0123456789abcdef01234567."#
+    };
+
+    assert_eq!(classify(commit), CommitKind::Revert);
+    assert_eq!(
+        reverted_commit(commit),
+        Some("0123456789abcdef0123456789abcdef01234567")
+    );
+}
+
+#[test]
+fn test_conventional_revert_is_not_git_generated() {
+    let commit = indoc! {"
+        revert: add flags
+
+        This reverts commit 0123456."
+    };
+
+    assert_eq!(classify(commit), CommitKind::Conventional);
+    assert_eq!(reverted_commit(commit), Some("0123456"));
+    assert_eq!(reverted_commit("revert: add flags"), None);
+}
@@ -12,6 +12,21 @@ fn test_parse_commit_type() {
     assert_eq!(parse_commit_type(commit).unwrap(), CommitType::Feature)
 }
 
+#[test]
+fn test_parse_perf_commit_type() {
+    let commit = "perf: cache the parsed grammar";
+
+    assert_eq!(parse_commit_type(commit).unwrap(), CommitType::Perf);
+    assert_eq!(
+        CommitType::from(CommitType::Perf.as_str()),
+        CommitType::Perf
+    );
+    assert_eq!(
+        CommitType::from("per"),
+        CommitType::Custom("per".to_string())
+    );
+}
+
 #[test]
 fn test_parse_invalid_commit_type() {
     let commit = "@: add a new feature";
@@ -174,3 +189,13 @@ fn test_parse_commit_complex() {
         },
     )
 }
+
+#[test]
+fn test_parse_error_location() {
+    let commit = "feat:a new feature";
+
+    let err = parse_commit(commit).unwrap_err();
+
+    assert_eq!(err.kind, ParseErrorKind::InvalidTokenSeparator);
+    assert_eq!(err.line_col(), (1, 5));
+}
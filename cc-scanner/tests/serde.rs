@@ -0,0 +1,43 @@
+#![cfg(feature = "serde")]
+
+use cc_scanner::{conventional_commit::ConventionalCommit, parse_commit};
+use indoc::indoc;
+use serde_json::json;
+
+#[test]
+fn test_serialize_commit() {
+    let commit = indoc! {"
+        feat(node)!: update @hapijs to v20
+
+        in anticipation of upgrade to node v14
+
+        Refs #123"
+    };
+
+    let value = serde_json::to_value(parse_commit(commit).unwrap()).unwrap();
+
+    assert_eq!(
+        value,
+        json!({
+            "commit_type": "feat",
+            "scope": { "noun": "node" },
+            "description": "update @hapijs to v20",
+            "body": "in anticipation of upgrade to node v14",
+            "footers": [{ "token": "Refs", "separator": "pound", "content": "123" }],
+            "is_breaking_change": true,
+        })
+    );
+}
+
+#[test]
+fn test_commit_round_trip() {
+    for message in ["perf: faster", "evolution: mewtwo", "fix(cli): a bug"] {
+        let commit = parse_commit(message).unwrap();
+        let json = serde_json::to_string(&commit).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<ConventionalCommit>(&json).unwrap(),
+            commit
+        );
+    }
+}
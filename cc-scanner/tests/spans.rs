@@ -0,0 +1,59 @@
+use cc_scanner::parse_commit_with_spans;
+use indoc::indoc;
+
+#[test]
+fn test_header_spans() {
+    let commit = "feat(node)!: update @hapijs to v20";
+
+    let spanned = parse_commit_with_spans(commit).unwrap();
+
+    assert_eq!(&commit[spanned.spans.header.clone()], commit);
+    assert_eq!(&commit[spanned.spans.commit_type.clone()], "feat");
+    assert_eq!(&commit[spanned.spans.scope.clone().unwrap()], "node");
+    assert_eq!(
+        &commit[spanned.spans.breaking_change_indicator.clone().unwrap()],
+        "!"
+    );
+    assert_eq!(
+        &commit[spanned.spans.description.clone()],
+        "update @hapijs to v20"
+    );
+    assert_eq!(spanned.spans.body, None);
+    assert!(spanned.spans.footers.is_empty());
+}
+
+#[test]
+fn test_body_and_footer_spans() {
+    let commit = indoc! {"
+        fix: prevent racing of requests
+
+        Introduce a request id.
+
+        Reviewed-by: Z
+        Refs #123"
+    };
+
+    let spanned = parse_commit_with_spans(commit).unwrap();
+    let spans = spanned.spans;
+
+    assert_eq!(spans.scope, None);
+    assert_eq!(
+        &commit[spans.body.clone().unwrap()],
+        "Introduce a request id."
+    );
+    assert_eq!(spans.footers.len(), 2);
+    assert_eq!(&commit[spans.footers[0].token.clone()], "Reviewed-by");
+    assert_eq!(&commit[spans.footers[0].separator.clone()], ": ");
+    assert_eq!(&commit[spans.footers[0].content.clone()], "Z");
+    assert_eq!(&commit[spans.footers[1].token.clone()], "Refs");
+    assert_eq!(&commit[spans.footers[1].content.clone()], "123");
+}
+
+#[test]
+fn test_spanned_commit_matches_parse_commit() {
+    let commit = "feat(cli): add spans\n\nBREAKING CHANGE: offsets everywhere";
+
+    let spanned = parse_commit_with_spans(commit).unwrap();
+
+    assert_eq!(spanned.commit, cc_scanner::parse_commit(commit).unwrap());
+}
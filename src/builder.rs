@@ -0,0 +1,411 @@
+use crate::{
+    check::cleanup_message,
+    config::{Config, ConfigPrompt},
+    fix, git,
+    infer::{self, Inference},
+    prompt::{body, breaking_change, commit_type, footers, issues, scope, subject, Prompts},
+};
+use cc_scanner::{
+    conventional_commit::{CommitType, ConventionalCommit, Footer, Separator},
+    parse_commit, parse_footer, parse_scope,
+};
+use colored::Colorize;
+use log::debug;
+use miette::miette;
+use std::{fs, io::IsTerminal, path::Path};
+
+/// Commit components supplied as flags to `baouncer commit`.
+#[derive(Debug, Clone, Default)]
+pub struct CommitArgs {
+    pub commit_type: Option<String>,
+    pub scope: Option<String>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+    pub breaking: bool,
+    /// Footers in `TOKEN=VALUE` form.
+    pub footers: Vec<String>,
+    pub yes: bool,
+}
+impl CommitArgs {
+    /// Whether any component was given as a flag, which switches off the prompt sequence.
+    pub fn is_non_interactive(&self) -> bool {
+        self.commit_type.is_some()
+            || self.scope.is_some()
+            || self.subject.is_some()
+            || self.body.is_some()
+            || self.breaking
+            || !self.footers.is_empty()
+            || self.yes
+    }
+}
+
+/// Whether prompts can be shown, i.e. stdin is attached to a terminal.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+/// Infers scopes from the staged changes, warning when they span several.
+///
+/// Inference is best effort: outside a repository nothing is inferred.
+fn staged_scopes(cfg: &Config) -> Inference {
+    let inference = match git::discover().and_then(|repo| infer::staged(&repo, cfg)) {
+        Ok(inference) => inference,
+        Err(err) => {
+            debug!("could not infer a scope: {}", err);
+            return Inference::default();
+        }
+    };
+
+    if inference.is_ambiguous() {
+        eprintln!(
+            "{} {}",
+            "staged changes span several scopes:".yellow(),
+            inference.names().join(", ")
+        );
+    }
+
+    inference
+}
+
+/// Builds a commit by walking through the configured prompts in order.
+///
+/// With `defaults`, e.g. the commit being amended, each prompt starts from the existing
+/// component, and components without a prompt are kept as they are.
+pub fn from_prompts(
+    cfg: &Config,
+    defaults: Option<&ConventionalCommit>,
+) -> Result<ConventionalCommit, miette::Report> {
+    let mut sorted_prompts: Vec<ConfigPrompt> = cfg.prompts.values().cloned().collect();
+
+    sorted_prompts.sort_by_key(|prompt| prompt.order);
+
+    let mut commit = defaults.cloned().unwrap_or_default();
+    // footers picked in the issues prompt, which the footers prompt must not drop
+    let mut picked: Vec<Footer> = vec![];
+
+    for prompt in sorted_prompts {
+        match prompt.kind {
+            Prompts::Type => {
+                let default = defaults.map(|defaults| &defaults.commit_type);
+
+                commit.set_commit_type(commit_type(cfg.commit_types.clone(), default)?);
+            }
+            Prompts::Scope => {
+                let inference = staged_scopes(cfg);
+                let suggested = match defaults {
+                    Some(defaults) => defaults.scope.as_ref().map(|scope| scope.noun.as_str()),
+                    None => inference.best(),
+                };
+
+                commit.scope = scope(
+                    &cfg.scopes,
+                    cfg.allow_custom_scopes,
+                    &cfg.scope_names(),
+                    suggested,
+                )?;
+            }
+            Prompts::Subject => {
+                let default = defaults.map(|defaults| defaults.description.as_str());
+
+                commit.set_description(subject(default)?);
+            }
+            Prompts::Body => {
+                let default = defaults.and_then(|defaults| defaults.body.as_deref());
+                let choice = body(default)?;
+
+                commit.body = if choice.is_empty() {
+                    None
+                } else {
+                    Some(choice)
+                };
+            }
+            Prompts::IsBreaking => {
+                let default = defaults.is_some_and(|defaults| defaults.is_breaking_change);
+
+                commit.set_breaking_change(breaking_change(default)?);
+            }
+            Prompts::Issues => {
+                let issues = issues()?;
+
+                picked.extend(issues.iter().cloned());
+                add_footers(&mut commit, issues);
+            }
+            Prompts::Footers => {
+                let default = defaults.map_or(&[][..], |defaults| &defaults.footers);
+                let entered = footers(default)?.unwrap_or_default();
+
+                replace_default_footers(&mut commit, default, &picked, entered);
+            }
+        }
+    }
+
+    Ok(commit)
+}
+
+fn parse_footer_flag(flag: &str) -> Result<Footer, miette::Report> {
+    let Some((token, content)) = flag.split_once('=') else {
+        return Err(miette!(
+            "Invalid footer {:?}, expected the form TOKEN=VALUE",
+            flag
+        ));
+    };
+
+    let footer = Footer {
+        token: token.trim().to_string(),
+        separator: Separator::Colon,
+        content: content.trim().to_string(),
+    };
+
+    // round-trip through the parser so invalid tokens are reported like any other footer
+    parse_footer(&format!("{}: {}", footer.token, footer.content))
+        .map_err(|error| miette::Error::new(error.inner.into_miette()))?;
+
+    Ok(footer)
+}
+
+/// Builds a commit from flags, prompting only for required components that were not given.
+///
+/// With `defaults`, e.g. the commit being amended, components that were not given are taken
+/// from it instead. Fails instead of prompting when stdin is not a terminal.
+pub fn from_args(
+    cfg: &Config,
+    args: &CommitArgs,
+    defaults: Option<&ConventionalCommit>,
+) -> Result<ConventionalCommit, miette::Report> {
+    let mut commit = ConventionalCommit::default();
+
+    if let Some(defaults) = defaults {
+        commit = defaults.clone();
+
+        if !args.footers.is_empty() {
+            commit.footers.clear();
+        }
+    }
+
+    match &args.commit_type {
+        Some(value) => commit.set_commit_type(CommitType::from(value.as_str())),
+        None if defaults.is_some() => {}
+        None if is_interactive() => {
+            commit.set_commit_type(commit_type(cfg.commit_types.clone(), None)?)
+        }
+        None => return Err(miette!("--type is required when not running in a terminal")),
+    }
+
+    if let Some(value) = &args.scope {
+        let scope =
+            parse_scope(value).map_err(|error| miette::Error::new(error.inner.into_miette()))?;
+
+        commit.set_scope(scope);
+    }
+
+    match &args.subject {
+        Some(value) => commit.set_description(value.clone()),
+        None if defaults.is_some() => {}
+        None if is_interactive() => commit.set_description(subject(None)?),
+        None => {
+            return Err(miette!(
+                "--subject is required when not running in a terminal"
+            ))
+        }
+    }
+
+    if let Some(value) = &args.body {
+        // an empty body clears the one being amended
+        commit.body = (!value.is_empty()).then(|| value.clone());
+    }
+
+    if args.breaking {
+        commit.set_breaking_change(true);
+    }
+
+    for flag in &args.footers {
+        commit.set_footer(parse_footer_flag(flag)?);
+    }
+
+    Ok(commit)
+}
+
+/// Whether two footers say the same thing, as tokens are case-insensitive.
+fn same_footer(footer: &Footer, other: &Footer) -> bool {
+    footer.token.eq_ignore_ascii_case(&other.token) && footer.content == other.content
+}
+
+/// Adds `footers` to the commit, skipping those it already has, such as the `Closes` footers of
+/// the commit being amended.
+fn add_footers(commit: &mut ConventionalCommit, footers: Vec<Footer>) {
+    for footer in footers {
+        if !commit
+            .footers
+            .iter()
+            .any(|added| same_footer(added, &footer))
+        {
+            commit.set_footer(footer);
+        }
+    }
+}
+
+/// Applies the footers entered in the footers prompt, which starts from `default`: the default
+/// footers taken out of it are removed, unless they were `picked` in another prompt such as
+/// issues, and the new ones are added.
+fn replace_default_footers(
+    commit: &mut ConventionalCommit,
+    default: &[Footer],
+    picked: &[Footer],
+    entered: Vec<Footer>,
+) {
+    let contains = |footers: &[Footer], footer: &Footer| {
+        footers.iter().any(|other| same_footer(other, footer))
+    };
+
+    commit.footers.retain(|footer| {
+        !contains(default, footer) || contains(picked, footer) || contains(&entered, footer)
+    });
+
+    add_footers(commit, entered);
+}
+
+/// Fills the message file `git` hands the `prepare-commit-msg` hook with the message `build`
+/// returns, when `git commit` left the message to be written and there is a terminal to prompt
+/// on. Merges, amends, squashes, templates and `-m` messages already have a message, given by
+/// `source`, so the file is left as is for them.
+///
+/// Text a chained hook already put in the file, such as a ticket reference, is kept below the
+/// new message.
+pub fn prepare_message_file(
+    path: &Path,
+    source: Option<&str>,
+    interactive: bool,
+    build: impl FnOnce() -> Result<String, miette::Report>,
+) -> Result<(), miette::Report> {
+    if source.is_some_and(|source| !source.is_empty()) || !interactive {
+        return Ok(());
+    }
+
+    let message = build()?;
+    let raw = fs::read_to_string(path).map_err(|err| miette!("{}", err))?;
+    let existing = cleanup_message(&raw);
+    let message = if existing.is_empty() {
+        message
+    } else {
+        format!("{}\n\n{}", message, existing)
+    };
+
+    // git's comment lines stay below the message, which opens in the editor as usual
+    fs::write(path, fix::rewrite(&raw, &message)).map_err(|err| miette!("{}", err))
+}
+
+/// Validates a built commit against the grammar, returning the parsed result.
+pub fn validate(commit: &mut ConventionalCommit) -> Result<ConventionalCommit, miette::Report> {
+    parse_commit(&commit.as_str()).map_err(|err| miette!("{}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_parsing_a_footer_flag_it_should_split_on_the_first_equals_sign() {
+        let footer = parse_footer_flag("Refs=a=b").unwrap();
+
+        assert_eq!(footer.token, "Refs");
+        assert_eq!(footer.content, "a=b");
+    }
+
+    #[test]
+    fn when_parsing_a_footer_flag_without_an_equals_sign_it_should_fail() {
+        assert!(parse_footer_flag("Refs").is_err());
+    }
+
+    #[test]
+    fn when_issues_are_picked_before_footers_it_should_keep_both_without_duplicates() {
+        let head = parse_commit("fix: handle eof\n\nRefs: #3\nCloses: #1").unwrap();
+        let mut commit = head.clone();
+        let picked = vec![
+            parse_footer_flag("closes=#1").unwrap(),
+            parse_footer_flag("closes=#2").unwrap(),
+        ];
+
+        add_footers(&mut commit, picked.clone());
+
+        assert_eq!(commit.footers.len(), 3);
+
+        // `Refs: #3` and `Closes: #1` were taken out of the footers prompt
+        replace_default_footers(
+            &mut commit,
+            &head.footers,
+            &picked,
+            vec![parse_footer_flag("Refs=#4").unwrap()],
+        );
+
+        let footers: Vec<String> = commit
+            .footers
+            .iter()
+            .map(|footer| format!("{}{}{}", footer.token, footer.separator, footer.content))
+            .collect();
+
+        assert_eq!(footers, vec!["Closes: #1", "closes: #2", "Refs: #4"]);
+    }
+
+    #[test]
+    fn when_all_required_flags_are_given_it_should_build_a_valid_commit() {
+        let cfg = crate::config::defaults();
+        let args = CommitArgs {
+            commit_type: Some("feat".to_string()),
+            scope: Some("cli".to_string()),
+            subject: Some("add flags".to_string()),
+            breaking: true,
+            footers: vec!["Refs=#12".to_string()],
+            ..CommitArgs::default()
+        };
+
+        let mut commit = from_args(&cfg, &args, None).unwrap();
+
+        assert_eq!(commit.as_str(), "feat(cli)!: add flags\n\nRefs: #12");
+        assert!(validate(&mut commit).is_ok());
+    }
+
+    #[test]
+    fn when_amending_with_flags_it_should_keep_components_that_were_not_given() {
+        let cfg = crate::config::defaults();
+        let head = parse_commit("fix(cli): hnadle eof\n\nsome context\n\nRefs: #3").unwrap();
+        let args = CommitArgs {
+            subject: Some("handle eof".to_string()),
+            ..CommitArgs::default()
+        };
+
+        let mut commit = from_args(&cfg, &args, Some(&head)).unwrap();
+
+        assert_eq!(
+            commit.as_str(),
+            "fix(cli): handle eof\n\nsome context\n\nRefs: #3"
+        );
+    }
+
+    #[test]
+    fn when_git_already_has_a_message_it_should_leave_the_message_file_untouched() {
+        let path = std::env::temp_dir().join(format!(
+            "baouncer-prepare-commit-msg-{}",
+            std::process::id()
+        ));
+        let raw = "ABC-12\n# Please enter the commit message\n";
+        let build = || Ok("feat: add x".to_string());
+
+        fs::write(&path, raw).unwrap();
+
+        for source in ["message", "merge", "commit", "template", "squash"] {
+            prepare_message_file(&path, Some(source), true, || unreachable!()).unwrap();
+        }
+        prepare_message_file(&path, None, false, || unreachable!()).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), raw);
+
+        prepare_message_file(&path, Some(""), true, build).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "feat: add x\n\nABC-12\n\n# Please enter the commit message\n"
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{
+    check::cleanup_message,
+    git,
+    workspace::{self, Member},
+};
+use cc_scanner::{
+    conventional_commit::{CommitType, ConventionalCommit},
+    parse_commit,
+};
+use git2::{Oid, Repository};
+use log::{debug, warn};
+use semver::Version;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BumpError {
+    #[error("Tag {tag:?} already exists")]
+    TagExists { tag: String },
+
+    #[error("Git error: {0}")]
+    Git(#[from] git2::Error),
+}
+
+/// The size of a version increment, ordered so the largest bump wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+impl From<&ConventionalCommit> for Bump {
+    fn from(commit: &ConventionalCommit) -> Self {
+        if commit.is_breaking_change {
+            return Bump::Major;
+        }
+
+        match commit.commit_type {
+            CommitType::Feature => Bump::Minor,
+            CommitType::Bug => Bump::Patch,
+            _ => Bump::None,
+        }
+    }
+}
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bump = match self {
+            Bump::None => "none",
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        };
+        write!(f, "{}", bump)
+    }
+}
+impl Bump {
+    /// Applies the bump to `version`.
+    ///
+    /// Before 1.0.0 the public API is not considered stable, so following the usual convention
+    /// a breaking change only bumps the minor version.
+    pub fn apply(&self, version: &Version) -> Version {
+        let bump = if version.major == 0 && *self == Bump::Major {
+            Bump::Minor
+        } else {
+            *self
+        };
+
+        match bump {
+            Bump::None => version.clone(),
+            Bump::Patch => Version::new(version.major, version.minor, version.patch + 1),
+            Bump::Minor => Version::new(version.major, version.minor + 1, 0),
+            Bump::Major => Version::new(version.major + 1, 0, 0),
+        }
+    }
+}
+
+/// A release tag whose name holds a semantic version, e.g. `v1.2.3`.
+#[derive(Debug, Clone)]
+pub struct ReleaseTag {
+    pub name: String,
+    pub version: Version,
+    pub target: Oid,
+}
+
+/// Parses a tag name of the form `<prefix>[v]<version>`, ignoring pre-release versions.
+pub fn parse_tag(name: &str, prefix: &str) -> Option<Version> {
+    let version = name.strip_prefix(prefix)?;
+    let version = version.strip_prefix('v').unwrap_or(version);
+
+    Version::parse(version)
+        .ok()
+        .filter(|version| version.pre.is_empty())
+}
+
+/// Finds the highest versioned tag with the given prefix that is reachable from HEAD.
+pub fn latest_tag(repo: &Repository, prefix: &str) -> Result<Option<ReleaseTag>, BumpError> {
+    let head = repo.head()?.peel_to_commit()?.id();
+
+    latest_tag_from(repo, prefix, head, true)
+}
+
+/// Finds the highest versioned tag with the given prefix that is reachable from `tip`, counting
+/// tags on `tip` itself only when `inclusive` is set.
+pub fn latest_tag_from(
+    repo: &Repository,
+    prefix: &str,
+    tip: Oid,
+    inclusive: bool,
+) -> Result<Option<ReleaseTag>, BumpError> {
+    let mut latest: Option<ReleaseTag> = None;
+
+    for name in repo.tag_names(None)?.iter().flatten() {
+        let Some(version) = parse_tag(name, prefix) else {
+            continue;
+        };
+
+        let target = repo
+            .revparse_single(&format!("refs/tags/{}", name))?
+            .peel_to_commit()?
+            .id();
+
+        let reachable = if target == tip {
+            inclusive
+        } else {
+            repo.graph_descendant_of(tip, target)?
+        };
+
+        if !reachable {
+            debug!("skipping tag {} as it is not reachable from {}", name, tip);
+            continue;
+        }
+
+        if latest
+            .as_ref()
+            .is_none_or(|latest| version > latest.version)
+        {
+            latest = Some(ReleaseTag {
+                name: name.to_string(),
+                version,
+                target,
+            });
+        }
+    }
+
+    Ok(latest)
+}
+
+/// Parses every commit message, skipping (with a warning) those that are not conventional.
+pub fn parse_commits(commits: Vec<(Oid, String)>) -> Vec<(Oid, ConventionalCommit)> {
+    commits
+        .into_iter()
+        .filter_map(
+            |(oid, message)| match parse_commit(&cleanup_message(&message)) {
+                Ok(commit) => Some((oid, commit)),
+                Err(_) => {
+                    warn!("skipping non-conventional commit {}", oid);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+/// The outcome of computing the next version.
+#[derive(Debug, Clone)]
+pub struct NextVersion {
+    pub previous: Option<ReleaseTag>,
+    pub current: Version,
+    pub next: Version,
+    pub bump: Bump,
+}
+impl NextVersion {
+    /// Name of the tag for the next version, following the previous tag's use of a `v`.
+    pub fn tag_name(&self, prefix: &str) -> String {
+        let v = match &self.previous {
+            Some(tag) => tag
+                .name
+                .strip_prefix(prefix)
+                .is_some_and(|name| name.starts_with('v')),
+            None => true,
+        };
+
+        format!("{}{}{}", prefix, if v { "v" } else { "" }, self.next)
+    }
+}
+
+/// Range of the commits since `previous`, or all commits up to HEAD without a previous release.
+fn range_since(previous: &Option<ReleaseTag>) -> String {
+    match previous {
+        Some(tag) => format!("{}..HEAD", tag.target),
+        None => "HEAD".to_string(),
+    }
+}
+
+fn compute(
+    previous: Option<ReleaseTag>,
+    commits: &[(Oid, ConventionalCommit)],
+    initial: Version,
+) -> NextVersion {
+    let bump = commits
+        .iter()
+        .map(|(_, commit)| Bump::from(commit))
+        .max()
+        .unwrap_or(Bump::None);
+
+    let current = previous
+        .as_ref()
+        .map(|tag| tag.version.clone())
+        .unwrap_or(initial);
+
+    NextVersion {
+        next: bump.apply(&current),
+        previous,
+        current,
+        bump,
+    }
+}
+
+/// Computes the next version from the commits since the latest release tag.
+///
+/// When no release tag exists, every commit is considered and the version starts at 0.0.0.
+pub fn next_version(repo: &Repository, prefix: &str) -> Result<NextVersion, BumpError> {
+    let previous = latest_tag(repo, prefix)?;
+    let range = range_since(&previous);
+    let commits = parse_commits(git::commits_in_range(repo, &range, false)?);
+
+    Ok(compute(previous, &commits, Version::new(0, 0, 0)))
+}
+
+/// Prefix of the release tags of a workspace crate, e.g. `cc-scanner-` for `cc-scanner-v1.2.3`.
+pub fn tag_prefix(member: &Member) -> String {
+    format!("{}-", member.name)
+}
+
+/// Returns the commits in `range` attributed to `member` by the paths they change.
+pub fn crate_commits(
+    repo: &Repository,
+    range: &str,
+    members: &[Member],
+    member: &Member,
+) -> Result<Vec<(Oid, String)>, git2::Error> {
+    let mut commits = vec![];
+
+    for (oid, message) in git::commits_in_range(repo, range, false)? {
+        let paths = git::commit_paths(repo, oid)?;
+
+        if workspace::attribute(members, &paths).contains(&member) {
+            commits.push((oid, message));
+        }
+    }
+
+    Ok(commits)
+}
+
+/// The next version of one crate of a Cargo workspace.
+#[derive(Debug, Clone)]
+pub struct CrateVersion {
+    pub member: Member,
+    pub next: NextVersion,
+}
+
+/// Computes the next version of every workspace crate from the commits touching it since its
+/// latest release tag.
+///
+/// Crate tags are named `<crate>-v<version>`. Without one, every commit is considered, starting
+/// from the version in the crate's manifest.
+pub fn next_crate_versions(
+    repo: &Repository,
+    members: &[Member],
+) -> Result<Vec<CrateVersion>, BumpError> {
+    let mut versions = vec![];
+
+    for member in members {
+        let previous = latest_tag(repo, &tag_prefix(member))?;
+        let range = range_since(&previous);
+        let commits = parse_commits(crate_commits(repo, &range, members, member)?);
+        let initial = member
+            .version
+            .clone()
+            .unwrap_or_else(|| Version::new(0, 0, 0));
+
+        versions.push(CrateVersion {
+            member: member.clone(),
+            next: compute(previous, &commits, initial),
+        });
+    }
+
+    Ok(versions)
+}
+
+/// Creates an annotated tag named `name` pointing at HEAD.
+pub fn create_tag(repo: &Repository, name: &str) -> Result<Oid, BumpError> {
+    if repo.revparse_single(&format!("refs/tags/{}", name)).is_ok() {
+        return Err(BumpError::TagExists {
+            tag: name.to_string(),
+        });
+    }
+
+    let head = repo.head()?.peel_to_commit()?;
+    let sig = repo.signature()?;
+
+    Ok(repo.tag(name, head.as_object(), &sig, name, false)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(message: &str) -> ConventionalCommit {
+        parse_commit(message).unwrap()
+    }
+
+    #[test]
+    fn when_classifying_commits_it_should_map_types_to_bumps() {
+        assert_eq!(Bump::from(&commit("feat: a")), Bump::Minor);
+        assert_eq!(Bump::from(&commit("fix: a")), Bump::Patch);
+        assert_eq!(Bump::from(&commit("chore: a")), Bump::None);
+        assert_eq!(Bump::from(&commit("chore!: a")), Bump::Major);
+        assert_eq!(
+            Bump::from(&commit("fix: a\n\nBREAKING CHANGE: gone")),
+            Bump::Major
+        );
+    }
+
+    #[test]
+    fn when_applying_a_bump_it_should_reset_lower_components() {
+        let version = Version::new(1, 2, 3);
+
+        assert_eq!(Bump::Patch.apply(&version), Version::new(1, 2, 4));
+        assert_eq!(Bump::Minor.apply(&version), Version::new(1, 3, 0));
+        assert_eq!(Bump::Major.apply(&version), Version::new(2, 0, 0));
+        assert_eq!(Bump::None.apply(&version), version);
+    }
+
+    #[test]
+    fn when_applying_a_major_bump_before_1_0_it_should_bump_minor() {
+        assert_eq!(
+            Bump::Major.apply(&Version::new(0, 4, 2)),
+            Version::new(0, 5, 0)
+        );
+    }
+
+    #[test]
+    fn when_parsing_tags_it_should_accept_an_optional_v_prefix() {
+        assert_eq!(parse_tag("v1.2.3", ""), Some(Version::new(1, 2, 3)));
+        assert_eq!(parse_tag("1.2.3", ""), Some(Version::new(1, 2, 3)));
+        assert_eq!(parse_tag("v1.2.3-rc.1", ""), None);
+        assert_eq!(parse_tag("release", ""), None);
+    }
+}
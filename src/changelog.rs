@@ -0,0 +1,398 @@
+use crate::{
+    bump::{self, BumpError},
+    config::CommitType as ConfigCommitType,
+    git,
+    workspace::Member,
+};
+use cc_scanner::conventional_commit::ConventionalCommit;
+use git2::{Oid, Repository};
+use std::{collections::HashMap, fs, io, path::Path};
+
+/// Order in which sections appear; types not listed follow alphabetically.
+const SECTION_ORDER: [&str; 11] = [
+    "feat", "fix", "perf", "refactor", "revert", "docs", "style", "test", "build", "ci", "chore",
+];
+
+/// A release section of the changelog.
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub title: String,
+    /// The release date, formatted as `YYYY-MM-DD`.
+    pub date: Option<String>,
+    pub commits: Vec<(Oid, ConventionalCommit)>,
+}
+
+/// Formats seconds since the Unix epoch as a `YYYY-MM-DD` date in UTC.
+fn format_date(seconds: i64) -> String {
+    // civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let days = seconds.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn short(oid: &Oid) -> String {
+    oid.to_string().chars().take(7).collect()
+}
+
+fn issues(commit: &ConventionalCommit) -> Vec<String> {
+    commit
+        .footers
+        .iter()
+        .filter(|footer| footer.token.eq_ignore_ascii_case("closes"))
+        .flat_map(|footer| {
+            footer
+                .content
+                .split(',')
+                .map(|issue| issue.trim().to_string())
+        })
+        .filter(|issue| !issue.is_empty())
+        .collect()
+}
+
+fn entry(oid: &Oid, commit: &ConventionalCommit, text: &str) -> String {
+    let mut line = String::from("- ");
+
+    if let Some(scope) = &commit.scope {
+        line.push_str(&format!("**{}:** ", scope.noun));
+    }
+
+    line.push_str(&format!("{} ({})", text, short(oid)));
+
+    let issues = issues(commit);
+
+    if !issues.is_empty() {
+        line.push_str(&format!(", closes {}", issues.join(", ")));
+    }
+
+    line
+}
+
+fn heading(commit_type: &str, commit_types: &HashMap<String, ConfigCommitType>) -> String {
+    match commit_types.get(commit_type) {
+        Some(ConfigCommitType {
+            description,
+            emoji: Some(emoji),
+            ..
+        }) => format!("{} {}", emoji, description),
+        Some(ConfigCommitType { description, .. }) => description.clone(),
+        None => commit_type.to_string(),
+    }
+}
+
+/// Renders a release as Markdown, grouping commits by type with breaking changes listed first.
+pub fn render(release: &Release, commit_types: &HashMap<String, ConfigCommitType>) -> String {
+    let mut markdown = match &release.date {
+        Some(date) => format!("## {} ({})\n", release.title, date),
+        None => format!("## {}\n", release.title),
+    };
+
+    let breaking: Vec<String> = release
+        .commits
+        .iter()
+        .filter(|(_, commit)| commit.is_breaking_change)
+        .flat_map(|(oid, commit)| {
+            let notes: Vec<String> = commit
+                .footers
+                .iter()
+                .filter(|footer| footer.is_breaking_change())
+                .map(|footer| entry(oid, commit, &footer.content))
+                .collect();
+
+            // a `!` without a footer has no separate note, so the description stands in
+            if notes.is_empty() {
+                vec![entry(oid, commit, &commit.description)]
+            } else {
+                notes
+            }
+        })
+        .collect();
+
+    if !breaking.is_empty() {
+        markdown.push_str("\n### ⚠️ BREAKING CHANGES\n\n");
+        markdown.push_str(&breaking.join("\n"));
+        markdown.push('\n');
+    }
+
+    let mut sections: HashMap<&str, Vec<String>> = HashMap::new();
+
+    for (oid, commit) in &release.commits {
+        sections
+            .entry(commit.commit_type.as_str())
+            .or_default()
+            .push(entry(oid, commit, &commit.description));
+    }
+
+    let mut types: Vec<&str> = sections.keys().copied().collect();
+    types.sort_by_key(|commit_type| {
+        (
+            SECTION_ORDER
+                .iter()
+                .position(|t| t == commit_type)
+                .unwrap_or(usize::MAX),
+            commit_type.to_string(),
+        )
+    });
+
+    for commit_type in types {
+        markdown.push_str(&format!("\n### {}\n\n", heading(commit_type, commit_types)));
+        markdown.push_str(&sections[commit_type].join("\n"));
+        markdown.push('\n');
+    }
+
+    markdown
+}
+
+/// Collects the commits between `from` (exclusive) and `to`, defaulting `from` to the latest
+/// tag with the given prefix before `to`, and keeping only those the `workspace` crate is
+/// attributed.
+fn collect(
+    repo: &Repository,
+    from: Option<&str>,
+    to: &str,
+    prefix: &str,
+    workspace: Option<(&[Member], &Member)>,
+) -> Result<Release, BumpError> {
+    let to_commit = repo.revparse_single(to)?.peel_to_commit()?;
+
+    let from = match from {
+        Some(from) => Some(repo.revparse_single(from)?.peel_to_commit()?.id()),
+        None => bump::latest_tag_from(repo, prefix, to_commit.id(), false)?.map(|tag| tag.target),
+    };
+
+    let range = match from {
+        Some(from) => format!("{}..{}", from, to_commit.id()),
+        None => to_commit.id().to_string(),
+    };
+
+    let commits = match workspace {
+        Some((members, member)) => bump::crate_commits(repo, &range, members, member)?,
+        None => git::commits_in_range(repo, &range, false)?,
+    };
+
+    let mut commits = bump::parse_commits(commits);
+
+    // newest first, as changelogs are read
+    commits.reverse();
+
+    // only a release has a date, the one of its tagged commit
+    let (title, date) = if repo.find_reference(&format!("refs/tags/{}", to)).is_ok() {
+        (
+            to.to_string(),
+            Some(format_date(to_commit.time().seconds())),
+        )
+    } else {
+        ("Unreleased".to_string(), None)
+    };
+
+    Ok(Release {
+        title,
+        date,
+        commits,
+    })
+}
+
+/// Collects the conventional commits between `from` (exclusive) and `to` into a release.
+///
+/// `from` defaults to the latest version tag reachable from `to`, other than `to` itself. The
+/// release is titled and dated after `to` when it names a tag, and is an undated "Unreleased"
+/// section otherwise.
+pub fn release(repo: &Repository, from: Option<&str>, to: &str) -> Result<Release, BumpError> {
+    collect(repo, from, to, "", None)
+}
+
+/// Collects the conventional commits touching one workspace crate into a release.
+///
+/// `from` defaults to the crate's latest release tag before `to`, and an unreleased section is
+/// titled after the crate.
+pub fn crate_release(
+    repo: &Repository,
+    members: &[Member],
+    member: &Member,
+    from: Option<&str>,
+    to: &str,
+) -> Result<Release, BumpError> {
+    let mut release = collect(
+        repo,
+        from,
+        to,
+        &bump::tag_prefix(member),
+        Some((members, member)),
+    )?;
+
+    if release.title == "Unreleased" {
+        release.title = format!("{}: Unreleased", member.name);
+    }
+
+    Ok(release)
+}
+
+/// Inserts `markdown` at the top of the changelog at `path`, below a leading `# ` title.
+pub fn prepend(path: &Path, markdown: &str) -> Result<(), io::Error> {
+    let existing = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+
+    let contents = match existing.split_once('\n') {
+        Some((title, rest)) if title.starts_with("# ") => {
+            format!(
+                "{}\n\n{}\n{}",
+                title,
+                markdown,
+                rest.trim_start_matches('\n')
+            )
+        }
+        _ if existing.is_empty() => markdown.to_string(),
+        _ => format!("{}\n{}", markdown, existing),
+    };
+
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit_file, init_repo};
+    use cc_scanner::parse_commit;
+    use std::path::PathBuf;
+
+    fn oid(n: u8) -> Oid {
+        Oid::from_bytes(&[n; 20]).unwrap()
+    }
+
+    fn commit_types() -> HashMap<String, ConfigCommitType> {
+        HashMap::from([(
+            "feat".to_string(),
+            ConfigCommitType {
+                name: "feat".to_string(),
+                description: "A new feature".to_string(),
+                emoji: Some("🎁".to_string()),
+            },
+        )])
+    }
+
+    #[test]
+    fn when_formatting_dates_it_should_handle_leap_years() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+    }
+
+    #[test]
+    fn when_rendering_a_release_it_should_group_commits_by_type() {
+        let release = Release {
+            title: "v1.0.0".to_string(),
+            date: Some("2024-01-01".to_string()),
+            commits: vec![
+                (oid(1), parse_commit("fix(cli): handle eof").unwrap()),
+                (
+                    oid(2),
+                    parse_commit("feat: add changelog\n\ncloses: #1, #2").unwrap(),
+                ),
+                (
+                    oid(3),
+                    parse_commit("feat(api)!: drop v1\n\nBREAKING CHANGE: v1 is gone").unwrap(),
+                ),
+            ],
+        };
+
+        let markdown = render(&release, &commit_types());
+
+        assert_eq!(
+            markdown,
+            "## v1.0.0 (2024-01-01)\n\
+             \n### ⚠️ BREAKING CHANGES\n\n\
+             - **api:** v1 is gone (0303030)\n\
+             \n### 🎁 A new feature\n\n\
+             - add changelog (0202020), closes #1, #2\n\
+             - **api:** drop v1 (0303030)\n\
+             \n### fix\n\n\
+             - **cli:** handle eof (0101010)\n"
+        );
+    }
+
+    fn tag(repo: &Repository, name: &str, oid: Oid) {
+        repo.tag_lightweight(name, &repo.find_object(oid, None).unwrap(), false)
+            .unwrap();
+    }
+
+    fn descriptions(release: &Release) -> Vec<&str> {
+        release
+            .commits
+            .iter()
+            .map(|(_, commit)| commit.description.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn when_head_is_past_a_release_it_should_still_collect_that_release() {
+        let (dir, repo) = init_repo("changelog-past");
+
+        tag(&repo, "v0.1.0", commit_file(&repo, "a", "feat: add a"));
+        commit_file(&repo, "b", "feat: add b");
+        tag(&repo, "v0.2.0", commit_file(&repo, "c", "fix: fix c"));
+        commit_file(&repo, "d", "feat: add d");
+
+        let past = release(&repo, None, "v0.2.0").unwrap();
+
+        assert_eq!(past.title, "v0.2.0");
+        assert!(past.date.is_some());
+        assert_eq!(descriptions(&past), vec!["fix c", "add b"]);
+
+        let unreleased = release(&repo, None, "HEAD").unwrap();
+
+        assert_eq!(unreleased.title, "Unreleased");
+        assert_eq!(unreleased.date, None);
+        assert_eq!(descriptions(&unreleased), vec!["add d"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_head_is_past_a_crate_release_it_should_still_collect_that_release() {
+        let (dir, repo) = init_repo("changelog-crate");
+        let member = |name: &str| Member {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            version: None,
+        };
+        let members = vec![member("cli"), member("core")];
+
+        fs::create_dir_all(dir.join("cli")).unwrap();
+        fs::create_dir_all(dir.join("core")).unwrap();
+
+        tag(
+            &repo,
+            "cli-v0.1.0",
+            commit_file(&repo, "cli/a", "feat: add a"),
+        );
+        commit_file(&repo, "core/x", "feat: add x");
+        tag(
+            &repo,
+            "cli-v0.2.0",
+            commit_file(&repo, "cli/b", "fix: fix b"),
+        );
+        commit_file(&repo, "cli/c", "feat: add c");
+
+        let past = crate_release(&repo, &members, &members[0], None, "cli-v0.2.0").unwrap();
+
+        assert_eq!(past.title, "cli-v0.2.0");
+        assert_eq!(descriptions(&past), vec!["fix b"]);
+
+        let unreleased = crate_release(&repo, &members, &members[0], None, "HEAD").unwrap();
+
+        assert_eq!(unreleased.title, "cli: Unreleased");
+        assert_eq!(descriptions(&unreleased), vec!["add c"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
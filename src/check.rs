@@ -0,0 +1,472 @@
+use crate::{
+    config::Config,
+    git,
+    lint::{self, LintDiagnostic, Violation},
+    report::{self, Failure, Format, Record, RuleViolation},
+};
+use cc_scanner::{
+    autosquash::split_autosquash,
+    conventional_commit::ConventionalCommit,
+    errors::ParseError,
+    kind::{classify, reverted_commit, CommitKind},
+    parse_commit, parse_commit_with_spans,
+};
+use colored::Colorize;
+use git2::{Oid, Repository};
+use log::info;
+use miette::miette;
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// Line git inserts above the diff in `git commit --verbose`; everything below it is dropped.
+pub(crate) const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+#[derive(Debug, Error, Clone)]
+pub enum CheckError {
+    #[error("I/O error reading {path:?}: {source}")]
+    IoError {
+        path: PathBuf,
+        #[source]
+        source: Arc<io::Error>,
+    },
+
+    #[error("I/O error reading commit message from stdin: {0}")]
+    StdinError(Arc<io::Error>),
+}
+
+/// Resolves the commit message to check, in order of precedence: the `-m` flag, a file path,
+/// or stdin (when no file is given, or the file is `-`).
+pub fn read_message(message: Option<&String>, file: Option<&String>) -> Result<String, CheckError> {
+    if let Some(message) = message {
+        return Ok(message.clone());
+    }
+
+    match file.map(String::as_str) {
+        Some("-") | None => {
+            let mut buffer = String::new();
+
+            io::stdin()
+                .read_to_string(&mut buffer)
+                .map_err(|err| CheckError::StdinError(Arc::new(err)))?;
+
+            Ok(buffer)
+        }
+        Some(path) => fs::read_to_string(path).map_err(|err| CheckError::IoError {
+            path: PathBuf::from(path),
+            source: Arc::new(err),
+        }),
+    }
+}
+
+/// Strips what git itself strips before recording a message: comment lines, anything below
+/// the scissors line, and surrounding blank lines.
+pub fn cleanup_message(raw: &str) -> String {
+    let mut lines: Vec<&str> = vec![];
+
+    for line in raw.lines() {
+        if line == SCISSORS {
+            break;
+        }
+
+        if !line.starts_with('#') {
+            lines.push(line.trim_end());
+        }
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
+/// Parses a commit message, returning the parser diagnostic as a report on failure.
+pub fn check_commit(message: &str) -> Result<ConventionalCommit, miette::Report> {
+    let message = cleanup_message(message);
+
+    match parse_commit(&message) {
+        Ok(commit) => {
+            info!("commit message is a valid conventional commit");
+
+            Ok(commit)
+        }
+        Err(error) => Err(parse_report(error)),
+    }
+}
+
+fn parse_report(error: ParseError) -> miette::Report {
+    let kind = error.kind.to_string();
+
+    miette::Error::new(error.inner.into_miette()).wrap_err(kind)
+}
+
+fn lint_reports(message: &str, violations: Vec<Violation>) -> Vec<miette::Report> {
+    violations
+        .into_iter()
+        .map(|violation| {
+            miette::Report::new(LintDiagnostic {
+                violation,
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Classifies `message`, only trusting the header of a generated message when what `git` leaves
+/// behind bears it out: a merge commit has several parents, and a revert names the commit it
+/// reverts, which must exist in `repo` when there is one.
+fn generated_kind(repo: Option<&Repository>, oid: Option<Oid>, message: &str) -> CommitKind {
+    let kind = classify(message);
+    let commit = repo
+        .zip(oid)
+        .and_then(|(repo, oid)| repo.find_commit(oid).ok());
+
+    let confirmed = match kind {
+        CommitKind::Conventional => true,
+        CommitKind::Merge => commit.is_none_or(|commit| commit.parent_count() > 1),
+        CommitKind::Revert => reverted_commit(message).is_some_and(|sha| {
+            repo.is_none_or(|repo| repo.revparse_single(&format!("{}^{{commit}}", sha)).is_ok())
+        }),
+    };
+
+    if confirmed {
+        kind
+    } else {
+        CommitKind::Conventional
+    }
+}
+
+/// Parses and lints a single message, returning its record and the diagnostics to display.
+///
+/// Autosquash messages (`fixup! `, `squash! `, `amend! `) are checked by the message they wrap.
+/// Merge and revert messages generated by `git` are accepted as is when the config allows them.
+fn check_one(
+    repo: Option<&Repository>,
+    oid: Option<Oid>,
+    raw_message: &str,
+    cfg: &Config,
+) -> (Record, Vec<miette::Report>) {
+    let message = cleanup_message(raw_message);
+    let (_, wrapped) = split_autosquash(&message);
+    let sha = oid.map(|oid| oid.to_string());
+    // positions are found in the wrapped message, the prefix only shifts the first line
+    let prefix = message.len() - wrapped.len();
+    let column = |line: usize, column: usize| if line == 1 { column + prefix } else { column };
+
+    if cfg.allows(generated_kind(repo, oid, wrapped)) {
+        let record = Record {
+            sha,
+            valid: true,
+            failure: None,
+            violations: vec![],
+            message: message.clone(),
+        };
+
+        return (record, vec![]);
+    }
+
+    match parse_commit_with_spans(wrapped) {
+        Ok(spanned) => {
+            let violations = lint::lint(wrapped, &spanned, &cfg.rules, &cfg.commit_type_names());
+
+            let record = Record {
+                sha,
+                valid: !lint::has_errors(&violations),
+                failure: None,
+                violations: violations
+                    .iter()
+                    .map(|violation| {
+                        let mut violation = RuleViolation::new(wrapped, violation);
+                        violation.column = column(violation.line, violation.column);
+
+                        violation
+                    })
+                    .collect(),
+                message: message.clone(),
+            };
+
+            (record, lint_reports(wrapped, violations))
+        }
+        Err(error) => {
+            let mut failure = Failure::from(&error);
+            failure.column = column(failure.line, failure.column);
+
+            let record = Record {
+                sha,
+                valid: false,
+                failure: Some(failure),
+                violations: vec![],
+                message: message.clone(),
+            };
+
+            (record, vec![parse_report(error)])
+        }
+    }
+}
+
+/// Parses and lints a single message, printing rule violations.
+///
+/// Returns an error when the message fails to parse or violates an error-level rule. A revert
+/// message is only accepted as is when the commit it names exists in `repo`.
+pub fn lint_commit(
+    repo: Option<&Repository>,
+    message: &str,
+    cfg: &Config,
+) -> Result<(), miette::Report> {
+    let (record, reports) = check_one(repo, None, message, cfg);
+
+    for report in reports {
+        eprintln!("{:?}", report);
+    }
+
+    if record.valid {
+        Ok(())
+    } else {
+        Err(miette!("commit message failed checks"))
+    }
+}
+
+/// Checks each message, printing the results in the requested format.
+///
+/// Every message is checked before returning, so a single run surfaces every offending commit.
+/// Machine-readable formats are written to stdout; text diagnostics go to stderr. Messages with
+/// a SHA are looked up in `repo` to tell genuine merges and reverts from lookalikes.
+pub fn check_messages(
+    repo: Option<&Repository>,
+    messages: Vec<(Option<Oid>, String)>,
+    format: Format,
+    cfg: &Config,
+) -> Result<(), miette::Report> {
+    let total = messages.len();
+    let mut records: Vec<Record> = vec![];
+    let mut reports: Vec<(Option<Oid>, Vec<miette::Report>)> = vec![];
+
+    for (oid, raw_message) in messages {
+        let (record, diagnostics) = check_one(repo, oid, &raw_message, cfg);
+
+        records.push(record);
+
+        if !diagnostics.is_empty() {
+            reports.push((oid, diagnostics));
+        }
+    }
+
+    match format {
+        Format::Text => {
+            for (oid, diagnostics) in reports {
+                if let Some(oid) = oid {
+                    eprintln!("{} {}", "commit".red(), oid.to_string().red());
+                }
+
+                for diagnostic in diagnostics {
+                    eprintln!("{:?}", diagnostic);
+                }
+            }
+        }
+        Format::Json => println!("{}", report::to_json(&records)),
+        Format::Sarif => println!("{}", report::to_sarif(&records)),
+        Format::Junit => print!("{}", report::to_junit(&records)),
+    }
+
+    let failures = records.iter().filter(|record| !record.valid).count();
+
+    if failures > 0 {
+        return Err(miette!(
+            "{} of {} commit messages failed checks",
+            failures,
+            total
+        ));
+    }
+
+    info!("all {} commit messages are valid", total);
+
+    Ok(())
+}
+
+/// Checks every commit in `range`, reporting each failure with its SHA.
+pub fn check_range(
+    repo: &Repository,
+    range: &str,
+    include_merges: bool,
+    format: Format,
+    cfg: &Config,
+) -> Result<(), miette::Report> {
+    let commits =
+        git::commits_in_range(repo, range, include_merges).map_err(|err| miette!("{}", err))?;
+
+    check_messages(
+        Some(repo),
+        commits
+            .into_iter()
+            .map(|(oid, message)| (Some(oid), message))
+            .collect(),
+        format,
+        cfg,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit, init_repo};
+
+    #[test]
+    fn when_cleaning_up_a_message_it_should_drop_comment_lines() {
+        let raw = "feat: add a thing\n# Please enter the commit message\n";
+
+        assert_eq!(cleanup_message(raw), "feat: add a thing");
+    }
+
+    #[test]
+    fn when_cleaning_up_a_message_it_should_drop_everything_below_the_scissors_line() {
+        let raw = format!("fix: a bug\n\n{}\ndiff --git a/x b/x\n", SCISSORS);
+
+        assert_eq!(cleanup_message(&raw), "fix: a bug");
+    }
+
+    #[test]
+    fn when_checking_a_valid_message_with_a_trailing_newline_it_should_succeed() {
+        assert!(check_commit("feat(cli): add check-commit\n").is_ok());
+    }
+
+    #[test]
+    fn when_checking_an_invalid_message_it_should_fail() {
+        assert!(check_commit("not a conventional commit").is_err());
+    }
+
+    #[test]
+    fn when_checking_a_fixup_commit_it_should_check_the_wrapped_message() {
+        let cfg = crate::config::defaults();
+
+        assert!(lint_commit(None, "fixup! fix(cli): handle eof", &cfg).is_ok());
+        assert!(lint_commit(None, "fixup! handle eof", &cfg).is_err());
+    }
+
+    #[test]
+    fn when_checking_a_fixup_commit_it_should_report_positions_in_the_full_message() {
+        let mut cfg = crate::config::defaults();
+        cfg.rules.merge(crate::lint::RulesConfig {
+            subject_full_stop: Some(crate::lint::RuleConfig {
+                level: crate::lint::Severity::Error,
+                value: None,
+            }),
+            ..Default::default()
+        });
+
+        let (record, _) = check_one(None, None, "fixup! fix: a bug.", &cfg);
+        let violation = &record.violations[0];
+
+        assert_eq!((violation.line, violation.column), (1, 18));
+
+        let (plain, _) = check_one(None, None, "fix a bug", &cfg);
+        let (record, _) = check_one(None, None, "fixup! fix a bug", &cfg);
+        let (plain, failure) = (plain.failure.unwrap(), record.failure.unwrap());
+
+        assert_eq!(failure.line, plain.line);
+        assert_eq!(failure.column, plain.column + "fixup! ".len());
+    }
+
+    #[test]
+    fn when_checking_git_generated_messages_it_should_apply_the_policy() {
+        let mut cfg = crate::config::defaults();
+        let revert = "Revert \"feat: add x\"\n\nThis reverts commit 0123456.";
+
+        assert!(lint_commit(None, "Merge branch 'topic'", &cfg).is_err());
+        assert!(lint_commit(None, revert, &cfg).is_err());
+
+        cfg.allow_merge_commits = true;
+        cfg.allow_revert_commits = true;
+
+        assert!(lint_commit(None, "Merge branch 'topic'", &cfg).is_ok());
+        assert!(lint_commit(None, revert, &cfg).is_ok());
+        // without the line `git revert` adds, it is only a lookalike
+        assert!(lint_commit(None, "Revert \"feat: add x\"", &cfg).is_err());
+    }
+
+    #[test]
+    fn when_linting_a_revert_in_a_repository_it_should_look_up_the_reverted_commit() {
+        let (dir, repo) = init_repo("lint-revert");
+        let mut cfg = crate::config::defaults();
+        cfg.allow_revert_commits = true;
+
+        let reverted = commit(&repo, "feat: add x");
+        let genuine = format!(
+            "Revert \"feat: add x\"\n\nThis reverts commit {}.",
+            reverted
+        );
+        let unknown = "Revert \"feat: add x\"\n\nThis reverts commit 0123456.";
+
+        assert!(lint_commit(Some(&repo), &genuine, &cfg).is_ok());
+        assert!(lint_commit(Some(&repo), unknown, &cfg).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_a_message_violates_an_error_rule_it_should_fail() {
+        let mut cfg = crate::config::defaults();
+
+        assert!(lint_commit(None, "fix: a bug.", &cfg).is_ok());
+
+        cfg.rules.merge(crate::lint::RulesConfig {
+            subject_full_stop: Some(crate::lint::RuleConfig {
+                level: crate::lint::Severity::Error,
+                value: None,
+            }),
+            ..Default::default()
+        });
+
+        assert!(lint_commit(None, "fix: a bug.", &cfg).is_err());
+    }
+
+    #[test]
+    fn when_checking_a_range_with_an_invalid_commit_it_should_fail() {
+        let (dir, repo) = init_repo("range");
+
+        let cfg = crate::config::defaults();
+
+        for message in ["feat: initial", "fix: a bug", "oops"] {
+            commit(&repo, message);
+        }
+
+        assert!(check_range(&repo, "HEAD~1..HEAD", false, Format::Text, &cfg).is_err());
+        assert!(check_range(&repo, "HEAD~2..HEAD~1", false, Format::Text, &cfg).is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_checking_a_range_it_should_only_accept_genuine_merges_and_reverts() {
+        let (dir, repo) = init_repo("generated");
+
+        let mut cfg = crate::config::defaults();
+        cfg.allow_merge_commits = true;
+        cfg.allow_revert_commits = true;
+
+        let reverted = commit(&repo, "feat: add x");
+        commit(
+            &repo,
+            &format!(
+                "Revert \"feat: add x\"\n\nThis reverts commit {}.",
+                reverted
+            ),
+        );
+
+        assert!(check_range(&repo, "HEAD~1..HEAD", false, Format::Text, &cfg).is_ok());
+
+        commit(
+            &repo,
+            "Revert \"feat: add y\"\n\nThis reverts commit 0123456789.",
+        );
+
+        assert!(check_range(&repo, "HEAD~1..HEAD", false, Format::Text, &cfg).is_err());
+
+        // a single parent makes it an ordinary commit, whatever its message says
+        commit(&repo, "Merge branch 'topic'");
+
+        assert!(check_range(&repo, "HEAD~1..HEAD", false, Format::Text, &cfg).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,7 +1,50 @@
+use crate::report::Format;
 use clap::{
     arg, crate_authors, crate_description, crate_name, crate_version, Arg, ArgAction, Command,
 };
 
+/// Output format shared by the checking subcommands
+fn format_arg() -> Arg {
+    Arg::new("format")
+        .long("format")
+        .help("Output format for check results")
+        .value_parser(Format::variants())
+        .default_value("text")
+}
+
+/// Per-crate mode shared by the release subcommands
+fn workspace_arg() -> Arg {
+    Arg::new("workspace")
+        .long("workspace")
+        .help("Handle each crate of the Cargo workspace separately")
+        .action(ArgAction::SetTrue)
+}
+
+/// Signing overrides shared by the subcommands that create commits
+fn sign_args() -> [Arg; 2] {
+    [
+        Arg::new("sign")
+            .short('S')
+            .long("sign")
+            .help("Sign the commit, regardless of commit.gpgsign")
+            .action(ArgAction::SetTrue),
+        Arg::new("no_sign")
+            .long("no-sign")
+            .help("Do not sign the commit, regardless of commit.gpgsign")
+            .conflicts_with("sign")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+/// Skips the hooks `git commit --no-verify` skips, shared by the subcommands that run them
+fn no_verify_arg() -> Arg {
+    Arg::new("no_verify")
+        .short('n')
+        .long("no-verify")
+        .help("Skip the pre-commit and commit-msg hooks")
+        .action(ArgAction::SetTrue)
+}
+
 /// Returns a clap command-line interface
 pub fn interface() -> Command {
     Command::new(crate_name!())
@@ -38,46 +81,229 @@ pub fn interface() -> Command {
         .arg(
             Arg::new("scope")
                 .long("scope")
-                .help("")
+                .help("Prompt for a scope")
                 .action(ArgAction::SetTrue)
                 .global(true),
         )
         .arg(
             Arg::new("body")
                 .long("body")
-                .help("")
+                .help("Prompt for a body")
                 .action(ArgAction::SetTrue)
                 .global(true),
         )
         .arg(
             Arg::new("is_breaking")
                 .long("is_breaking")
-                .help("")
+                .help("Prompt for whether the change is breaking")
                 .action(ArgAction::SetTrue)
                 .global(true),
         )
         .arg(
             Arg::new("footers")
                 .long("footers")
-                .help("")
+                .help("Prompt for footers")
                 .action(ArgAction::SetTrue)
                 .global(true),
         )
         .arg(
             Arg::new("issues")
                 .long("issues")
-                .help("")
+                .help("Prompt for issues closed by the commit")
                 .action(ArgAction::SetTrue)
                 .global(true),
         )
-        .subcommand(Command::new("commit").about("Create a conventional commit"))
+        .subcommand(
+            Command::new("commit")
+                .about("Create a conventional commit")
+                .long_about(
+                    "Create a conventional commit. Without flags, every configured prompt is \
+                     shown; when any component is given as a flag, only missing required \
+                     components (type and subject) are prompted for.",
+                )
+                .args(vec![
+                    arg!(-t --type <TYPE> "Commit type, e.g. feat"),
+                    // `--scope` and `--body` are the switches enabling the prompts
+                    arg!(-s --"scope-name" <SCOPE> "Commit scope, e.g. cli"),
+                    arg!(--subject <SUBJECT> "Short description of the change"),
+                    arg!(-b --"body-text" <BODY> "Longer description of the change"),
+                    Arg::new("breaking")
+                        .long("breaking")
+                        .help("Mark the commit as a breaking change")
+                        .action(ArgAction::SetTrue),
+                    Arg::new("footer")
+                        .long("footer")
+                        .value_name("TOKEN=VALUE")
+                        .help("Add a footer, may be repeated")
+                        .action(ArgAction::Append),
+                    Arg::new("yes")
+                        .short('y')
+                        .long("yes")
+                        .help("Commit without asking for confirmation")
+                        .action(ArgAction::SetTrue),
+                    Arg::new("amend")
+                        .long("amend")
+                        .help("Rewrite HEAD, starting from its message, with the staged changes")
+                        .action(ArgAction::SetTrue),
+                    Arg::new("allow_empty")
+                        .long("allow-empty")
+                        .help("Allow a commit that records no change, e.g. for a release")
+                        .action(ArgAction::SetTrue),
+                    no_verify_arg(),
+                    Arg::new("fixup")
+                        .long("fixup")
+                        .value_name("REV")
+                        .help("Create a `fixup!` commit for REV, picked from recent history if omitted")
+                        .num_args(0..=1)
+                        .default_missing_value("")
+                        .conflicts_with_all([
+                            "type",
+                            "scope-name",
+                            "subject",
+                            "body-text",
+                            "breaking",
+                            "footer",
+                            "amend",
+                        ]),
+                ])
+                .args(sign_args()),
+        )
+        .subcommand(
+            Command::new("reword")
+                .about("Rewrite the message of a past commit, replaying the commits after it")
+                .arg(arg!(<REV> "The commit to reword, e.g. HEAD~2"))
+                .args(sign_args()),
+        )
+        .subcommand(
+            Command::new("revert")
+                .about("Revert a commit with a conventional `revert:` commit")
+                .args(vec![
+                    arg!(<REV> "The commit to revert, e.g. HEAD~2"),
+                    Arg::new("yes")
+                        .short('y')
+                        .long("yes")
+                        .help("Commit without asking for confirmation")
+                        .action(ArgAction::SetTrue),
+                    no_verify_arg(),
+                ])
+                .args(sign_args()),
+        )
         .subcommand(
             Command::new("commit-msg-hook")
-                .about("Create a pre-commit hook to check conventional commits"),
+                .about("Install a commit-msg hook that checks conventional commits")
+                .arg(
+                    Arg::new("uninstall")
+                        .long("uninstall")
+                        .help("Remove the hook, restoring any hook it replaced")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("prepare-commit-msg-hook")
+                .about("Install a prepare-commit-msg hook that runs the commit wizard on `git commit`")
+                .arg(
+                    Arg::new("uninstall")
+                        .long("uninstall")
+                        .help("Remove the hook, restoring any hook it replaced")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("prepare-commit-msg")
+                .about("Run the commit wizard from the prepare-commit-msg hook")
+                .args(vec![
+                    arg!(<FILE> "The commit message file"),
+                    arg!([SOURCE] "Where the message comes from, empty when it is left to be written"),
+                    arg!([SHA] "The commit being amended, if any"),
+                ]),
         )
         .subcommand(
             Command::new("check-commit")
-                .about("Create a pre-commit hook to check conventional commits")
-                .args(vec![arg!(-m --message <MESSAGE>)]),
+                .about("Check whether a commit message is a conventional commit")
+                .args(vec![
+                    arg!(-m --message <MESSAGE> "Commit message to check"),
+                    arg!([FILE] "File containing the commit message, `-` for stdin")
+                        .conflicts_with("message"),
+                    Arg::new("fix")
+                        .long("fix")
+                        .help("Rewrite FILE in place, fixing common mistakes before checking")
+                        .requires("FILE")
+                        .conflicts_with("message")
+                        .action(ArgAction::SetTrue),
+                    format_arg(),
+                ]),
+        )
+        .subcommand(
+            Command::new("check-range")
+                .about("Check every commit in a revision range, e.g. `main..HEAD`")
+                .args(vec![
+                    arg!(<RANGE> "Revision range (`A..B`) or a single revision to check"),
+                    Arg::new("include_merges")
+                        .long("include-merges")
+                        .help("Also check merge commits, which are skipped by default")
+                        .action(ArgAction::SetTrue),
+                    format_arg(),
+                ]),
+        )
+        .subcommand(
+            Command::new("bump")
+                .about("Print the next semantic version based on commits since the latest tag")
+                .args(vec![
+                    Arg::new("tag")
+                        .long("tag")
+                        .help("Create an annotated tag for the next version at HEAD")
+                        .action(ArgAction::SetTrue),
+                    workspace_arg(),
+                ]),
         )
+        .subcommand(
+            Command::new("changelog")
+                .about("Generate a Markdown changelog from conventional commits")
+                .args(vec![
+                    arg!(--from <REV> "Exclusive start of the range, defaults to the latest version tag before --to"),
+                    arg!(--to <REV> "Inclusive end of the range").default_value("HEAD"),
+                    arg!(--prepend <FILE> "Prepend the changelog to FILE instead of printing it"),
+                    workspace_arg(),
+                ]),
+        )
+        .subcommand(
+            Command::new("parse")
+                .about("Parse a conventional commit message and print its components")
+                .args(vec![
+                    arg!(-m --message <MESSAGE> "Commit message to parse"),
+                    arg!([FILE] "File containing the commit message, `-` for stdin")
+                        .conflicts_with("message"),
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the parsed commit as JSON")
+                        .action(ArgAction::SetTrue),
+                ]),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_committing_it_should_tell_prompt_switches_from_components() {
+        let matches = interface().get_matches_from([
+            "baouncer",
+            "commit",
+            "--scope",
+            "--is_breaking",
+            "--scope-name",
+            "cli",
+        ]);
+        let commit = matches.subcommand_matches("commit").unwrap();
+
+        assert!(matches.get_flag("scope"));
+        assert!(matches.get_flag("is_breaking"));
+        assert!(!matches.get_flag("body"));
+        assert_eq!(
+            commit.get_one::<String>("scope-name").map(String::as_str),
+            Some("cli")
+        );
+        assert!(commit.get_one::<String>("body-text").is_none());
+    }
 }
@@ -1,4 +1,9 @@
-use crate::prompt::Prompts;
+use crate::{
+    lint::{RuleConfig, RulesConfig, Severity},
+    prompt::Prompts,
+    workspace::Member,
+};
+use cc_scanner::kind::CommitKind;
 use log::debug;
 use miette::miette;
 use serde::Deserialize;
@@ -17,15 +22,32 @@ pub struct CommitType {
     pub description: String,
     pub emoji: Option<String>,
 }
+/// An entry of the `[[scopes]]` catalogue.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct Scope {
+    pub name: String,
+    pub description: String,
+    /// Globs of the paths this scope covers, relative to the repository root.
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
 #[derive(Debug, Deserialize, Clone)]
 pub struct TomlPrompt {
     pub name: String,
     pub order: usize,
 }
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct TomlConfig {
     pub commit_types: Option<Vec<CommitType>>,
     pub prompts: Vec<TomlPrompt>,
+    pub rules: Option<RulesConfig>,
+    pub scopes: Option<Vec<Scope>>,
+    /// Whether scopes outside the `[[scopes]]` catalogue may be used.
+    pub allow_custom_scopes: Option<bool>,
+    /// Whether merge commit messages generated by `git` or a forge pass checks.
+    pub allow_merge_commits: Option<bool>,
+    /// Whether revert commit messages generated by `git revert` pass checks.
+    pub allow_revert_commits: Option<bool>,
 }
 #[derive(Debug, Clone)]
 pub struct ConfigPrompt {
@@ -46,6 +68,14 @@ pub struct ConfigArgs {
 pub struct Config {
     pub commit_types: HashMap<String, CommitType>,
     pub prompts: HashMap<String, ConfigPrompt>,
+    pub rules: RulesConfig,
+    pub scopes: Vec<Scope>,
+    pub allow_custom_scopes: bool,
+    pub allow_merge_commits: bool,
+    pub allow_revert_commits: bool,
+    /// Whether a config file sets the `scope-enum` rule, whose level then wins over the one
+    /// the scope catalogue implies.
+    scope_enum_configured: bool,
 }
 impl Config {
     fn new(args: ConfigArgs) -> Self {
@@ -177,6 +207,140 @@ impl Config {
         Self {
             commit_types: commit_types_hash,
             prompts: prompts_hash,
+            rules: RulesConfig::defaults(),
+            scopes: vec![],
+            allow_custom_scopes: false,
+            scope_enum_configured: false,
+            allow_merge_commits: false,
+            allow_revert_commits: false,
+        }
+    }
+
+    /// Returns the names of all configured commit types, sorted.
+    pub fn commit_type_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.commit_types.keys().cloned().collect();
+
+        names.sort();
+
+        names
+    }
+
+    /// Returns the scopes listed by the `scope-enum` rule, whether or not it is enabled. The
+    /// `[[scopes]]` catalogue is only among them once `apply_scope_catalogue` has run, as it has
+    /// for any configuration returned by `init`.
+    pub fn scope_names(&self) -> Vec<String> {
+        self.rules
+            .scope_enum
+            .as_ref()
+            .and_then(|rule| rule.value.clone())
+            .unwrap_or_default()
+    }
+
+    fn merge_rules(&mut self, config: TomlConfig) {
+        if let Some(rules) = config.rules {
+            self.scope_enum_configured |= rules.scope_enum.is_some();
+            self.rules.merge(rules);
+        }
+    }
+
+    /// Makes every crate of a Cargo workspace a valid scope, adding crates missing from the
+    /// scope catalogue to it so they can be picked.
+    pub fn add_workspace_members(&mut self, members: &[Member]) {
+        if members.is_empty() {
+            return;
+        }
+
+        if !self.scopes.is_empty() {
+            for member in members {
+                if !self.scopes.iter().any(|scope| scope.name == member.name) {
+                    self.scopes.push(Scope {
+                        name: member.name.clone(),
+                        description: format!("The {} crate", member.name),
+                        paths: vec![],
+                    });
+                }
+            }
+        }
+
+        let mut names = self.scope_names();
+
+        for member in members {
+            if !names.contains(&member.name) {
+                names.push(member.name.clone());
+            }
+        }
+
+        let level = self
+            .rules
+            .scope_enum
+            .as_ref()
+            .map_or(Severity::Off, |rule| rule.level);
+
+        self.rules.scope_enum = Some(RuleConfig {
+            level,
+            value: Some(names),
+        });
+    }
+
+    fn merge_scopes(&mut self, config: TomlConfig) {
+        if let Some(scopes) = config.scopes {
+            for scope in scopes {
+                match self.scopes.iter_mut().find(|s| s.name == scope.name) {
+                    Some(existing) => *existing = scope,
+                    None => self.scopes.push(scope),
+                }
+            }
+        }
+
+        if let Some(allow_custom_scopes) = config.allow_custom_scopes {
+            self.allow_custom_scopes = allow_custom_scopes;
+        }
+    }
+
+    /// Folds the scope catalogue into the `scope-enum` rule, so that `check-commit` enforces
+    /// it. Unless custom scopes are allowed, using any other scope is an error, or whatever
+    /// level the configured `scope-enum` rule has.
+    fn apply_scope_catalogue(&mut self) {
+        if self.scopes.is_empty() {
+            return;
+        }
+
+        let mut names = self.scope_names();
+
+        for scope in &self.scopes {
+            if !names.contains(&scope.name) {
+                names.push(scope.name.clone());
+            }
+        }
+
+        let level = match &self.rules.scope_enum {
+            Some(rule) if self.scope_enum_configured => rule.level,
+            _ if self.allow_custom_scopes => Severity::Off,
+            _ => Severity::Error,
+        };
+
+        self.rules.scope_enum = Some(RuleConfig {
+            level,
+            value: Some(names),
+        });
+    }
+
+    /// Whether checks accept a message of the given kind without it being a conventional commit.
+    pub fn allows(&self, kind: CommitKind) -> bool {
+        match kind {
+            CommitKind::Conventional => false,
+            CommitKind::Merge => self.allow_merge_commits,
+            CommitKind::Revert => self.allow_revert_commits,
+        }
+    }
+
+    fn merge_policy(&mut self, config: TomlConfig) {
+        if let Some(allow_merge_commits) = config.allow_merge_commits {
+            self.allow_merge_commits = allow_merge_commits;
+        }
+
+        if let Some(allow_revert_commits) = config.allow_revert_commits {
+            self.allow_revert_commits = allow_revert_commits;
         }
     }
 
@@ -319,6 +483,12 @@ pub fn init(args: ConfigArgs) -> Result<Config, ConfigError> {
 
                     base_config.merge_commit_types(cfg.clone());
 
+                    base_config.merge_rules(cfg.clone());
+
+                    base_config.merge_scopes(cfg.clone());
+
+                    base_config.merge_policy(cfg.clone());
+
                     base_config.merge_prompts(cfg);
                 }
                 Err(toml_error) => {
@@ -343,14 +513,164 @@ pub fn init(args: ConfigArgs) -> Result<Config, ConfigError> {
         }
     }
 
+    base_config.apply_scope_catalogue();
+
     Ok(base_config)
 }
 
+/// The configuration `init` starts from, without reading any `.baouncer.toml`, so tests do not
+/// depend on the files of the machine they run on.
+#[cfg(test)]
+pub(crate) fn defaults() -> Config {
+    let mut config = Config::new(ConfigArgs {
+        conventional_types: false,
+        scope: false,
+        body: false,
+        is_breaking: false,
+        footers: false,
+        issues: false,
+    });
+    config.apply_scope_catalogue();
+
+    config
+}
+
 // TODO: Are unit tests in the file still the thing to do
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn scope(name: &str, description: &str) -> Scope {
+        Scope {
+            name: name.to_string(),
+            description: description.to_string(),
+            paths: vec![],
+        }
+    }
+
+    #[test]
+    fn when_merging_scopes_it_should_override_scopes_by_name() {
+        let mut config = Config::new(default_args());
+
+        config.merge_scopes(TomlConfig {
+            scopes: Some(vec![
+                scope("cli", "Command line"),
+                scope("parser", "Parser"),
+            ]),
+            ..TomlConfig::default()
+        });
+        config.merge_scopes(TomlConfig {
+            scopes: Some(vec![scope("cli", "The command line interface")]),
+            allow_custom_scopes: Some(true),
+            ..TomlConfig::default()
+        });
+
+        assert_eq!(config.scopes.len(), 2);
+        assert_eq!(config.scopes[0].description, "The command line interface");
+        assert!(config.allow_custom_scopes);
+    }
+
+    #[test]
+    fn when_custom_scopes_are_not_allowed_it_should_enforce_the_catalogue() {
+        let mut config = Config::new(default_args());
+
+        config.merge_scopes(TomlConfig {
+            scopes: Some(vec![scope("cli", "Command line")]),
+            ..TomlConfig::default()
+        });
+        config.apply_scope_catalogue();
+
+        let rule = config.rules.scope_enum.clone().unwrap();
+
+        assert_eq!(rule.level, Severity::Error);
+        assert_eq!(config.scope_names(), vec!["cli".to_string()]);
+    }
+
+    #[test]
+    fn when_the_scope_enum_level_is_configured_the_catalogue_should_keep_it() {
+        let mut config = Config::new(default_args());
+
+        config.merge_rules(
+            toml::from_str("prompts = []\n[rules.scope-enum]\nlevel = \"warn\"").unwrap(),
+        );
+        config.merge_scopes(TomlConfig {
+            scopes: Some(vec![scope("cli", "Command line")]),
+            ..TomlConfig::default()
+        });
+        config.apply_scope_catalogue();
+
+        let rule = config.rules.scope_enum.clone().unwrap();
+
+        assert_eq!(rule.level, Severity::Warn);
+        assert_eq!(rule.value, Some(vec!["cli".to_string()]));
+    }
+
+    #[test]
+    fn when_adding_workspace_members_they_should_become_valid_scopes() {
+        let mut config = Config::new(default_args());
+
+        config.merge_scopes(TomlConfig {
+            scopes: Some(vec![scope("docs", "Documentation")]),
+            ..TomlConfig::default()
+        });
+        config.apply_scope_catalogue();
+        config.add_workspace_members(&[Member {
+            name: "cc-scanner".to_string(),
+            path: PathBuf::from("cc-scanner"),
+            version: None,
+        }]);
+
+        assert_eq!(config.scopes.len(), 2);
+        assert_eq!(
+            config.scope_names(),
+            vec!["docs".to_string(), "cc-scanner".to_string()]
+        );
+    }
+
+    #[test]
+    fn when_parsing_a_scope_catalogue_paths_should_be_optional() {
+        let config: TomlConfig = toml::from_str(
+            r#"
+            prompts = []
+            allow_custom_scopes = true
+
+            [[scopes]]
+            name = "parser"
+            description = "The cc-scanner crate"
+            paths = ["cc-scanner/**"]
+
+            [[scopes]]
+            name = "docs"
+            description = "Documentation"
+            "#,
+        )
+        .unwrap();
+
+        let scopes = config.scopes.unwrap();
+
+        assert_eq!(scopes[0].paths, vec!["cc-scanner/**".to_string()]);
+        assert!(scopes[1].paths.is_empty());
+    }
+
+    #[test]
+    fn when_merge_commits_are_disallowed_only_reverts_should_be_allowed() {
+        let mut config = Config::new(default_args());
+
+        assert!(!config.allows(CommitKind::Merge));
+        assert!(!config.allows(CommitKind::Revert));
+
+        config.merge_policy(
+            toml::from_str(
+                "prompts = []\nallow_merge_commits = false\nallow_revert_commits = true",
+            )
+            .unwrap(),
+        );
+
+        assert!(!config.allows(CommitKind::Merge));
+        assert!(config.allows(CommitKind::Revert));
+        assert!(!config.allows(CommitKind::Conventional));
+    }
+
     fn default_args() -> ConfigArgs {
         ConfigArgs {
             conventional_types: false,
@@ -413,6 +733,7 @@ mod tests {
                 emoji: Some("🌞".to_string()),
             }]),
             prompts: vec![],
+            ..TomlConfig::default()
         };
 
         let feat_type = config.commit_types.get("feat").unwrap();
@@ -437,6 +758,7 @@ mod tests {
                 emoji: Some("📚".to_string()),
             }]),
             prompts: vec![],
+            ..TomlConfig::default()
         };
 
         config.merge_commit_types(toml_config);
@@ -456,6 +778,7 @@ mod tests {
                 name: "type".to_string(),
                 order: 5,
             }],
+            ..TomlConfig::default()
         };
 
         config.merge_prompts(toml_config);
@@ -474,6 +797,7 @@ mod tests {
                 name: "footer".to_string(),
                 order: 0,
             }],
+            ..TomlConfig::default()
         };
 
         assert_eq!(config.prompts.len(), 2);
@@ -506,6 +830,7 @@ mod tests {
                 },
             ],
             commit_types: Some(vec![]),
+            ..TomlConfig::default()
         };
 
         let result = validate_config(toml_config);
@@ -533,6 +858,7 @@ mod tests {
                 },
             ],
             commit_types: Some(vec![]),
+            ..TomlConfig::default()
         };
 
         let result = validate_config(toml_config);
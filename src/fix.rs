@@ -0,0 +1,263 @@
+use crate::{check::SCISSORS, config::Config, lint::Severity};
+use cc_scanner::{autosquash::split_autosquash, conventional_commit::CommitType};
+
+/// Common misspellings of commit types, mapped to the type they stand for.
+const ALIASES: [(&str, &str); 12] = [
+    ("feature", "feat"),
+    ("features", "feat"),
+    ("bug", "fix"),
+    ("bugfix", "fix"),
+    ("fixes", "fix"),
+    ("hotfix", "fix"),
+    ("documentation", "doc"),
+    ("tests", "test"),
+    ("testing", "test"),
+    ("performance", "perf"),
+    ("refactoring", "refactor"),
+    ("chores", "chore"),
+];
+
+/// A corrected commit message and a description of each correction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixed {
+    pub message: String,
+    pub fixes: Vec<String>,
+}
+impl Fixed {
+    pub fn is_changed(&self) -> bool {
+        !self.fixes.is_empty()
+    }
+}
+
+/// Whether `commit_type` is configured or one the parser knows, spelled as it spells it, such as
+/// `doc`.
+fn is_known(commit_type: &str, commit_types: &[String]) -> bool {
+    commit_types.iter().any(|known| known == commit_type)
+        || !matches!(CommitType::from(commit_type), CommitType::Custom(_))
+}
+
+fn fix_commit_type(commit_type: &str, commit_types: &[String]) -> String {
+    let lower = commit_type.to_ascii_lowercase();
+
+    if is_known(&lower, commit_types) {
+        return lower;
+    }
+
+    ALIASES
+        .iter()
+        .find(|(alias, target)| *alias == lower && is_known(target, commit_types))
+        .map(|(_, target)| target.to_string())
+        .unwrap_or(lower)
+}
+
+/// Normalises the header, or returns `None` when it is too far off to be fixed mechanically.
+fn fix_header(
+    header: &str,
+    commit_types: &[String],
+    full_stop: bool,
+    fixes: &mut Vec<String>,
+) -> Option<String> {
+    let header = header.trim_start();
+    let type_end = header
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(header.len());
+
+    if type_end == 0 {
+        return None;
+    }
+
+    let colon = type_end + header[type_end..].find(':')?;
+    let commit_type = &header[..type_end];
+    let between = &header[type_end..colon];
+    let after_colon = &header[colon + 1..];
+
+    // only scope and breaking change indicator may sit between the type and the colon
+    let compact: String = between.chars().filter(|c| !c.is_whitespace()).collect();
+    let scope_ok = match compact.trim_end_matches('!') {
+        "" => true,
+        scope => {
+            scope.starts_with('(')
+                && scope.ends_with(')')
+                && scope[1..scope.len() - 1]
+                    .chars()
+                    .all(|c| c != '(' && c != ')')
+        }
+    };
+
+    if !scope_ok || compact.matches('!').count() > 1 {
+        return None;
+    }
+
+    let mut description = after_colon.trim();
+
+    if description.is_empty() {
+        return None;
+    }
+
+    let fixed_type = fix_commit_type(commit_type, commit_types);
+
+    if fixed_type != commit_type {
+        fixes.push(format!(
+            "replaced commit type {:?} with {:?}",
+            commit_type, fixed_type
+        ));
+    }
+
+    if compact != between {
+        fixes.push("removed whitespace before the colon".to_string());
+    }
+
+    if after_colon.strip_prefix(' ') != Some(description) {
+        fixes.push("added a single space after the colon".to_string());
+    }
+
+    if full_stop && description.ends_with('.') && !description.ends_with("..") {
+        description = &description[..description.len() - 1];
+        fixes.push("removed the trailing period".to_string());
+    }
+
+    Some(format!("{}{}: {}", fixed_type, compact, description))
+}
+
+/// Proposes a corrected version of a cleaned up commit message.
+///
+/// Addresses the mechanical mistakes the parser rejects (`InvalidTokenSeparator` for a missing
+/// space after the colon, `InvalidCommitType` for leading whitespace) as well as uppercase or
+/// aliased commit types and, unless the `subject-full-stop` rule is off, a trailing period.
+/// Autosquash prefixes are kept and the message they wrap is fixed.
+pub fn fix(message: &str, cfg: &Config) -> Fixed {
+    let mut fixes = vec![];
+    let (_, wrapped) = split_autosquash(message);
+    let prefix = &message[..message.len() - wrapped.len()];
+    let (header, rest) = wrapped.split_once('\n').unwrap_or((wrapped, ""));
+
+    let full_stop = cfg
+        .rules
+        .subject_full_stop
+        .as_ref()
+        .is_some_and(|rule| rule.level != Severity::Off);
+
+    let Some(header) = fix_header(header, &cfg.commit_type_names(), full_stop, &mut fixes) else {
+        return Fixed {
+            message: message.to_string(),
+            fixes,
+        };
+    };
+
+    let mut fixed = format!("{}{}", prefix, header);
+
+    if !rest.trim().is_empty() {
+        if !rest.starts_with('\n') {
+            fixes.push("added a blank line after the header".to_string());
+            fixed.push('\n');
+        }
+
+        fixed.push('\n');
+        fixed.push_str(rest);
+    }
+
+    Fixed {
+        message: fixed,
+        fixes,
+    }
+}
+
+/// Replaces the message in the raw contents of a commit message file, keeping git's comment
+/// lines and everything from the scissors line on (the diff of `git commit --verbose`).
+pub fn rewrite(raw: &str, fixed: &str) -> String {
+    let (message, diff) = match raw.find(SCISSORS) {
+        Some(start) => raw.split_at(start),
+        None => (raw, ""),
+    };
+    let comments: Vec<&str> = message
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .chain(diff.lines())
+        .collect();
+
+    if comments.is_empty() {
+        format!("{}\n", fixed)
+    } else {
+        format!("{}\n\n{}\n", fixed, comments.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+
+    fn cfg() -> Config {
+        config::defaults()
+    }
+
+    #[test]
+    fn when_the_header_is_valid_it_should_change_nothing() {
+        let fixed = fix("feat(cli)!: add a flag\n\nbody", &cfg());
+
+        assert!(!fixed.is_changed());
+        assert_eq!(fixed.message, "feat(cli)!: add a flag\n\nbody");
+    }
+
+    #[test]
+    fn when_the_type_is_uppercase_or_an_alias_it_should_use_the_configured_type() {
+        assert_eq!(fix("Feat: add a flag", &cfg()).message, "feat: add a flag");
+        assert_eq!(
+            fix("feature: add a flag", &cfg()).message,
+            "feat: add a flag"
+        );
+        assert_eq!(fix("BugFix: handle eof", &cfg()).message, "fix: handle eof");
+    }
+
+    #[test]
+    fn when_the_type_is_one_the_parser_knows_it_should_keep_its_spelling() {
+        assert!(!fix("doc: fix a typo", &cfg()).is_changed());
+        assert_eq!(fix("DOC: fix a typo", &cfg()).message, "doc: fix a typo");
+        assert_eq!(
+            fix("documentation: fix a typo", &cfg()).message,
+            "doc: fix a typo"
+        );
+    }
+
+    #[test]
+    fn when_the_separator_is_malformed_it_should_normalise_it() {
+        let fixed = fix("fix (cli) :handle eof.\nmore detail", &cfg());
+
+        assert_eq!(fixed.message, "fix(cli): handle eof\n\nmore detail");
+        assert_eq!(fixed.fixes.len(), 4);
+        assert!(cc_scanner::parse_commit(&fixed.message).is_ok());
+    }
+
+    #[test]
+    fn when_the_header_has_no_colon_it_should_not_guess() {
+        assert!(!fix("add a flag", &cfg()).is_changed());
+    }
+
+    #[test]
+    fn when_the_message_is_a_fixup_it_should_fix_the_wrapped_message() {
+        assert_eq!(
+            fix("fixup! Feat:add a flag", &cfg()).message,
+            "fixup! feat: add a flag"
+        );
+    }
+
+    #[test]
+    fn when_rewriting_a_message_file_it_should_keep_comments() {
+        let raw = "Feat: x\n# Please enter the commit message\n# ------------------------ >8 ------------------------\ndiff";
+
+        assert_eq!(
+            rewrite(raw, "feat: x"),
+            "feat: x\n\n# Please enter the commit message\n# ------------------------ >8 ------------------------\ndiff\n"
+        );
+    }
+
+    #[test]
+    fn when_the_message_continues_after_a_comment_it_should_not_keep_it_twice() {
+        let raw = "Feat: x\n# Please enter the commit message\n\nbody\n# On branch main";
+
+        assert_eq!(
+            rewrite(raw, "feat: x\n\nbody"),
+            "feat: x\n\nbody\n\n# Please enter the commit message\n# On branch main\n"
+        );
+    }
+}
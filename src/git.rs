@@ -1,50 +1,597 @@
+use crate::{
+    hooks::{self, HookError},
+    sign::{self, SignError, Signer},
+};
 use cc_scanner::conventional_commit::ConventionalCommit;
 use colored::Colorize;
-use git2::{Oid, Reference, Repository};
+use git2::{Commit, ErrorCode, Oid, Repository, Signature, Sort, StatusOptions, Tree};
+use std::{fs, path::PathBuf};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum CommitError {
-    /// The working tree has unstaged changes, preventing commit.
-    #[error("Unstaged changes exist, cannot commit")]
-    UnstagedChanges,
+    /// Nothing is staged and the working tree is clean.
+    #[error("Nothing to commit, the working tree is clean (use --allow-empty to commit anyway)")]
+    NothingToCommit,
+
+    /// Nothing is staged, but tracked files have changes.
+    #[error("Nothing staged, but {} tracked file(s) have unstaged changes, stage them with `git add`", paths.len())]
+    UnstagedChanges { paths: Vec<PathBuf> },
+
+    /// Nothing is staged, but there are new files `git` does not track yet.
+    #[error("Nothing staged, but {} untracked file(s) exist, add them with `git add`", paths.len())]
+    UntrackedFiles { paths: Vec<PathBuf> },
+
+    /// A hook of the repository failed or could not be run.
+    #[error("{0}")]
+    Hook(#[from] HookError),
+
+    /// The commit could not be signed with the configured program.
+    #[error("Signing error: {0}")]
+    Sign(#[from] SignError),
+
+    /// The commit to sign is not valid UTF-8, e.g. it keeps an author in a legacy encoding.
+    #[error("Cannot sign a commit that is not valid UTF-8")]
+    NotUtf8,
 
     /// A Git-specific error from the `git2` crate.
     #[error("Git error: {0}")]
     Git(#[from] git2::Error),
 }
 
-fn has_unstaged_changes(head: Reference<'_>, tree_id: Oid) -> Result<bool, CommitError> {
-    let head_commit = head.peel_to_commit()?;
-    let head_tree_id = head_commit.tree_id();
+/// Whether committing `tree` would record no change, i.e. it is the tree of HEAD or, on an
+/// unborn branch, is empty.
+fn is_empty_commit(tip: Option<&Commit>, tree: &Tree) -> bool {
+    match tip {
+        Some(tip) => tip.tree_id() == tree.id(),
+        None => tree.is_empty(),
+    }
+}
+
+/// How new commits are created.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommitOptions {
+    /// Whether to sign the commit, `commit.gpgsign` decides when `None`.
+    pub sign: Option<bool>,
+    /// Whether a commit recording no change may be created.
+    pub allow_empty: bool,
+    /// Whether to skip the `pre-commit` and `commit-msg` hooks.
+    pub no_verify: bool,
+}
+
+/// Runs the `pre-commit` hook, unless `options.no_verify` is set.
+///
+/// As with `git commit`, this comes before the message is written, so formatters run by the
+/// hook can restage files before the scope is inferred from them.
+pub fn pre_commit(repo: &Repository, options: CommitOptions) -> Result<(), CommitError> {
+    if !options.no_verify {
+        hooks::run(repo, "pre-commit", &[])?;
+    }
+
+    Ok(())
+}
+
+/// Passes `message` through the `prepare-commit-msg` and, unless `options.no_verify` is set,
+/// `commit-msg` hooks, returning the message they leave in `COMMIT_EDITMSG`.
+///
+/// `source` are the arguments `git` gives `prepare-commit-msg` after the file, describing where
+/// the message comes from.
+fn message_hooks(
+    repo: &Repository,
+    message: &str,
+    source: &[&str],
+    options: CommitOptions,
+) -> Result<String, CommitError> {
+    let path = repo.path().join("COMMIT_EDITMSG");
+    let file = path.to_string_lossy().to_string();
+    let io_error = |err| HookError::IoError {
+        path: path.clone(),
+        source: std::sync::Arc::new(err),
+    };
+
+    fs::write(&path, format!("{}\n", message)).map_err(io_error)?;
+
+    let mut args = vec![file.as_str()];
+    args.extend(source);
+
+    hooks::run(repo, "prepare-commit-msg", &args)?;
+
+    if !options.no_verify {
+        hooks::run(repo, "commit-msg", &[&file])?;
+    }
+
+    let edited = fs::read_to_string(&path).map_err(io_error)?;
+
+    // as with `git commit -m`, comment lines are part of the message and only whitespace is
+    // cleaned up
+    Ok(edited.trim_end().to_string())
+}
+
+/// Runs the `post-commit` hook, whose failure cannot undo the commit and is only reported.
+fn post_commit(repo: &Repository) {
+    if let Err(err) = hooks::run(repo, "post-commit", &[]) {
+        eprintln!("{} {}", "post-commit:".yellow(), err);
+    }
+}
+
+/// The paths that differ between HEAD, the index and the working tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Status {
+    pub staged: Vec<PathBuf>,
+    pub unstaged: Vec<PathBuf>,
+    pub untracked: Vec<PathBuf>,
+}
+impl Status {
+    pub fn has_staged(&self) -> bool {
+        !self.staged.is_empty()
+    }
+}
+
+/// Reports the status of `repo`, ignoring files ignored by `.gitignore`.
+pub fn status(repo: &Repository) -> Result<Status, git2::Error> {
+    let mut options = StatusOptions::new();
 
-    Ok(head_tree_id == tree_id)
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+
+    let staged_flags = git2::Status::INDEX_NEW
+        | git2::Status::INDEX_MODIFIED
+        | git2::Status::INDEX_DELETED
+        | git2::Status::INDEX_RENAMED
+        | git2::Status::INDEX_TYPECHANGE;
+    let unstaged_flags = git2::Status::WT_MODIFIED
+        | git2::Status::WT_DELETED
+        | git2::Status::WT_RENAMED
+        | git2::Status::WT_TYPECHANGE;
+
+    let mut status = Status::default();
+
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let path = PathBuf::from(String::from_utf8_lossy(entry.path_bytes()).to_string());
+        let flags = entry.status();
+
+        if flags.intersects(staged_flags) {
+            status.staged.push(path.clone());
+        }
+        if flags.intersects(unstaged_flags) {
+            status.unstaged.push(path.clone());
+        }
+        if flags.contains(git2::Status::WT_NEW) {
+            status.untracked.push(path);
+        }
+    }
+
+    Ok(status)
+}
+
+/// Explains why there is nothing to commit, pointing at unstaged changes first, then at
+/// untracked files.
+pub fn nothing_staged(status: Status) -> CommitError {
+    if !status.unstaged.is_empty() {
+        CommitError::UnstagedChanges {
+            paths: status.unstaged,
+        }
+    } else if !status.untracked.is_empty() {
+        CommitError::UntrackedFiles {
+            paths: status.untracked,
+        }
+    } else {
+        CommitError::NothingToCommit
+    }
 }
 
-pub fn commit(mut commit: ConventionalCommit) -> Result<Oid, CommitError> {
+/// Stages the changes to tracked files, including deletions, as `git add --update` does.
+pub fn stage_tracked(repo: &Repository) -> Result<(), git2::Error> {
+    let mut index = repo.index()?;
+
+    index.update_all(["*"], None)?;
+    index.write()
+}
+
+/// Opens the repository containing the current directory.
+pub fn discover() -> Result<Repository, git2::Error> {
     let current_dir = std::env::current_dir().expect("");
-    let repo = Repository::discover(&current_dir)?;
+
+    Repository::discover(current_dir)
+}
+
+/// Returns the id and message of every commit in `range`, oldest first.
+///
+/// `range` is either `A..B` (commits reachable from `B` but not `A`) or a single revision, in
+/// which case all of its ancestors are returned. Merge commits are skipped unless
+/// `include_merges` is set.
+pub fn commits_in_range(
+    repo: &Repository,
+    range: &str,
+    include_merges: bool,
+) -> Result<Vec<(Oid, String)>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+    if range.contains("..") {
+        walk.push_range(range)?;
+    } else {
+        walk.push(repo.revparse_single(range)?.peel_to_commit()?.id())?;
+    }
+
+    let mut commits = vec![];
+
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+
+        if commit.parent_count() > 1 && !include_merges {
+            continue;
+        }
+
+        let message = String::from_utf8_lossy(commit.message_bytes()).to_string();
+
+        commits.push((commit.id(), message));
+    }
+
+    Ok(commits)
+}
+
+/// Returns the paths of the changes staged in the index, relative to the repository root.
+pub fn staged_paths(repo: &Repository) -> Result<Vec<PathBuf>, git2::Error> {
+    // without a HEAD (an unborn branch) everything in the index is staged
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Returns the paths a commit changed relative to its first parent, or all of its paths for a
+/// root commit.
+pub fn commit_paths(repo: &Repository, oid: Oid) -> Result<Vec<PathBuf>, git2::Error> {
+    let commit = repo.find_commit(oid)?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Returns the message of the commit HEAD points to.
+pub fn head_message(repo: &Repository) -> Result<String, git2::Error> {
+    let head = repo.head()?.peel_to_commit()?;
+
+    Ok(String::from_utf8_lossy(head.message_bytes()).to_string())
+}
+
+/// Creates a commit without updating any reference.
+///
+/// The commit is signed when `sign` is set or, if it is `None`, when `commit.gpgsign` is, with
+/// the program and key `git` would use.
+pub fn create_commit(
+    repo: &Repository,
+    author: &Signature,
+    committer: &Signature,
+    message: &str,
+    tree: &Tree,
+    parents: &[&Commit],
+    sign: Option<bool>,
+) -> Result<Oid, CommitError> {
+    let config = repo.config()?;
+
+    if !sign::should_sign(&config, sign) {
+        return Ok(repo.commit(None, author, committer, message, tree, parents)?);
+    }
+
+    let buffer = repo.commit_create_buffer(author, committer, message, tree, parents)?;
+    let content = std::str::from_utf8(&buffer).map_err(|_| CommitError::NotUtf8)?;
+    let signature = Signer::from_config(&config)?.sign(content)?;
+
+    Ok(repo.commit_signed(content, &signature, None)?)
+}
+
+/// Points HEAD, or the branch it refers to, at `oid`.
+fn update_head(repo: &Repository, oid: Oid, log_message: &str) -> Result<(), git2::Error> {
+    let head = repo.find_reference("HEAD")?;
+
+    match head.symbolic_target() {
+        Some(branch) => repo.reference(branch, oid, true, log_message).map(|_| ()),
+        None => repo.set_head_detached(oid),
+    }
+}
+
+/// Rewrites HEAD with `commit` as its message and the current index as its tree.
+///
+/// The original author is kept, while the committer is updated, as `git commit --amend` does.
+pub fn amend(mut commit: ConventionalCommit, options: CommitOptions) -> Result<Oid, CommitError> {
+    let repo = discover()?;
     let sig = repo.signature()?;
     let tree_id = repo.index()?.write_tree()?;
     let tree = repo.find_tree(tree_id)?;
-    let head = repo.head()?;
-    let head_target = head.target().expect("Cannot get HEAD target");
-    let tip = repo.find_commit(head_target)?;
-    let parents = &[&tip];
+    let head = repo.head()?.peel_to_commit()?;
+    let parents: Vec<Commit> = head.parents().collect();
+    let parents: Vec<&Commit> = parents.iter().collect();
+    let head_id = head.id().to_string();
+    let message = message_hooks(&repo, &commit.as_str(), &["commit", &head_id], options)?;
 
-    if has_unstaged_changes(head, tree_id)? {
-        Err(CommitError::UnstagedChanges)
-    } else {
-        let oid = repo.commit(Some("HEAD"), &sig, &sig, &commit.as_str(), &tree, parents)?;
+    let oid = create_commit(
+        &repo,
+        &head.author(),
+        &sig,
+        &message,
+        &tree,
+        &parents,
+        options.sign,
+    )?;
 
-        println!(
-            "{} {} {}",
-            "commit".yellow(),
-            oid.to_string().yellow(),
-            "created".yellow()
+    update_head(
+        &repo,
+        oid,
+        &format!("commit (amend): {}", summary(&message)),
+    )?;
+
+    println!(
+        "{} {} {}",
+        "commit".yellow(),
+        oid.to_string().yellow(),
+        "amended".yellow()
+    );
+
+    post_commit(&repo);
+
+    Ok(oid)
+}
+
+/// The first line of `message`, as used in reflog entries.
+fn summary(message: &str) -> &str {
+    message.lines().next().unwrap_or_default()
+}
+
+/// Returns the id and message of the last `limit` commits reachable from HEAD, newest first.
+pub fn recent_commits(repo: &Repository, limit: usize) -> Result<Vec<(Oid, String)>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+
+    walk.set_sorting(Sort::TOPOLOGICAL)?;
+    walk.push_head()?;
+
+    let mut commits = vec![];
+
+    for oid in walk.take(limit) {
+        let commit = repo.find_commit(oid?)?;
+        let message = String::from_utf8_lossy(commit.message_bytes()).to_string();
+
+        commits.push((commit.id(), message));
+    }
+
+    Ok(commits)
+}
+
+pub fn commit(mut commit: ConventionalCommit, options: CommitOptions) -> Result<Oid, CommitError> {
+    commit_message(&commit.as_str(), options)
+}
+
+/// Commits the current index on top of HEAD with `message` as is, e.g. for `fixup!` commits,
+/// which are not conventional commits themselves.
+pub fn commit_message(message: &str, options: CommitOptions) -> Result<Oid, CommitError> {
+    commit_index(&discover()?, message, options)
+}
+
+/// Returns the commit HEAD points to, or `None` on an unborn branch, e.g. in a repository
+/// without commits.
+pub fn head_commit(repo: &Repository) -> Result<Option<Commit<'_>>, git2::Error> {
+    match repo.head() {
+        Ok(head) => head.peel_to_commit().map(Some),
+        Err(err) if err.code() == ErrorCode::UnbornBranch || err.code() == ErrorCode::NotFound => {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Commits the index of `repo` on top of HEAD, or as a root commit on an unborn branch.
+///
+/// Unless `options.allow_empty` is set, fails with an explanation when nothing is staged. The
+/// message goes through the repository's message hooks, see [`pre_commit`] for the first one
+/// of the chain. On a detached HEAD, the commit is made and HEAD moved to it, with a warning
+/// that no branch contains it.
+pub fn commit_index(
+    repo: &Repository,
+    message: &str,
+    options: CommitOptions,
+) -> Result<Oid, CommitError> {
+    let sig = repo.signature()?;
+    let tree_id = repo.index()?.write_tree()?;
+    let tree = repo.find_tree(tree_id)?;
+    let tip = head_commit(repo)?;
+    let parents: Vec<&Commit> = tip.iter().collect();
+
+    if !options.allow_empty && is_empty_commit(tip.as_ref(), &tree) {
+        return Err(nothing_staged(status(repo)?));
+    }
+
+    if repo.head_detached()? {
+        eprintln!(
+            "{} the commit will not be on any branch, run `git switch -c <branch>` to keep it",
+            "HEAD is detached:".yellow()
+        );
+    }
+
+    let message = message_hooks(repo, message, &["message"], options)?;
+    let oid = create_commit(repo, &sig, &sig, &message, &tree, &parents, options.sign)?;
+    let log_message = match tip {
+        Some(_) => format!("commit: {}", summary(&message)),
+        None => format!("commit (initial): {}", summary(&message)),
+    };
+
+    update_head(repo, oid, &log_message)?;
+
+    println!(
+        "{} {} {}",
+        "commit".yellow(),
+        oid.to_string().yellow(),
+        "created".yellow()
+    );
+
+    post_commit(repo);
+
+    Ok(oid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{init_repo, stage};
+    use std::fs;
+
+    #[test]
+    fn when_the_branch_is_unborn_it_should_create_a_root_commit() {
+        let (dir, repo) = init_repo("git-root");
+
+        assert!(head_commit(&repo).unwrap().is_none());
+        assert!(matches!(
+            commit_index(&repo, "feat: a", CommitOptions::default()),
+            Err(CommitError::NothingToCommit)
+        ));
+
+        stage(&repo, "a", "a");
+        let oid = commit_index(&repo, "feat: a", CommitOptions::default()).unwrap();
+
+        let head = head_commit(&repo).unwrap().unwrap();
+
+        assert_eq!(head.id(), oid);
+        assert_eq!(head.parent_count(), 0);
+        assert!(repo.head().unwrap().is_branch());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_head_is_detached_it_should_move_head_to_the_commit() {
+        let (dir, repo) = init_repo("git-detached");
+
+        stage(&repo, "a", "a");
+        let root = commit_index(&repo, "feat: a", CommitOptions::default()).unwrap();
+        let branch = repo.head().unwrap().name().unwrap().to_string();
+
+        repo.set_head_detached(root).unwrap();
+        stage(&repo, "b", "b");
+        let oid = commit_index(&repo, "feat: b", CommitOptions::default()).unwrap();
+
+        assert!(repo.head_detached().unwrap());
+        assert_eq!(repo.head().unwrap().target(), Some(oid));
+        assert_eq!(repo.refname_to_id(&branch).unwrap(), root);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_nothing_is_staged_it_should_name_the_problem() {
+        let (dir, repo) = init_repo("git-status");
+
+        stage(&repo, "a", "a");
+        commit_index(&repo, "feat: a", CommitOptions::default()).unwrap();
+
+        fs::write(dir.join("b"), "b").unwrap();
+
+        assert!(matches!(
+            commit_index(&repo, "feat: b", CommitOptions::default()),
+            Err(CommitError::UntrackedFiles { paths }) if paths == vec![PathBuf::from("b")]
+        ));
+
+        fs::write(dir.join("a"), "changed").unwrap();
+
+        assert!(matches!(
+            commit_index(&repo, "feat: b", CommitOptions::default()),
+            Err(CommitError::UnstagedChanges { paths }) if paths == vec![PathBuf::from("a")]
+        ));
+
+        stage_tracked(&repo).unwrap();
+
+        assert_eq!(
+            status(&repo).unwrap(),
+            Status {
+                staged: vec![PathBuf::from("a")],
+                unstaged: vec![],
+                untracked: vec![PathBuf::from("b")],
+            }
         );
 
-        Ok(oid)
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_empty_commits_are_allowed_it_should_commit_the_same_tree() {
+        let (dir, repo) = init_repo("git-empty");
+
+        stage(&repo, "a", "a");
+        let root = commit_index(&repo, "feat: a", CommitOptions::default()).unwrap();
+
+        let options = CommitOptions {
+            allow_empty: true,
+            ..CommitOptions::default()
+        };
+        let oid = commit_index(&repo, "chore: release 1.0.0", options).unwrap();
+
+        let commit = repo.find_commit(oid).unwrap();
+
+        assert_eq!(commit.parent_id(0).unwrap(), root);
+        assert_eq!(commit.tree_id(), repo.find_commit(root).unwrap().tree_id());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn when_message_hooks_exist_they_should_run_and_may_edit_the_message() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (dir, repo) = init_repo("git-hooks");
+        let hooks = repo.path().join("hooks");
+        let write_hook = |name: &str, script: &str| {
+            let path = hooks.join(name);
+            fs::write(&path, script).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        };
+
+        fs::create_dir_all(&hooks).unwrap();
+        write_hook(
+            "prepare-commit-msg",
+            "#!/bin/sh\n[ \"$2\" = message ] && printf '\\nRefs: #1\\n' >> \"$1\"\n",
+        );
+        write_hook("commit-msg", "#!/bin/sh\ngrep -q 'Refs: #1' \"$1\"\n");
+        write_hook("post-commit", "#!/bin/sh\nexit 1\n");
+
+        stage(&repo, "a", "a");
+        let oid = commit_index(&repo, "feat: a", CommitOptions::default()).unwrap();
+
+        assert_eq!(
+            repo.find_commit(oid).unwrap().message(),
+            Some("feat: a\n\nRefs: #1")
+        );
+
+        write_hook("commit-msg", "#!/bin/sh\nexit 1\n");
+        stage(&repo, "b", "b");
+
+        assert!(matches!(
+            commit_index(&repo, "feat: b", CommitOptions::default()),
+            Err(CommitError::Hook(HookError::Rejected { .. }))
+        ));
+
+        let options = CommitOptions {
+            no_verify: true,
+            ..CommitOptions::default()
+        };
+
+        assert!(commit_index(&repo, "feat: b", options).is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,357 @@
+use colored::Colorize;
+use git2::Repository;
+use log::debug;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// Marker written into every hook baouncer installs, used to recognise its own hooks.
+const MARKER: &str = "# baouncer:managed";
+
+/// Suffix given to a pre-existing hook that baouncer moved aside and now chains to.
+const BACKUP_SUFFIX: &str = "local";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum HookError {
+    #[error("I/O error writing {path:?}: {source}")]
+    IoError {
+        path: PathBuf,
+        #[source]
+        source: Arc<io::Error>,
+    },
+
+    #[error("A hook already exists at {hook:?} and its backup location {backup:?} is taken, refusing to overwrite either")]
+    BackupExists { hook: PathBuf, backup: PathBuf },
+
+    #[error("The hook at {path:?} was not installed by baouncer, leaving it in place")]
+    ForeignHook { path: PathBuf },
+
+    #[error("Failed to run the hook at {path:?}: {source}")]
+    RunError {
+        path: PathBuf,
+        #[source]
+        source: Arc<io::Error>,
+    },
+
+    /// The hook exited with a failure, e.g. a formatter or secret scanner rejected the commit.
+    #[error("The {hook} hook failed with {status}, aborting the commit")]
+    Rejected { hook: String, status: ExitStatus },
+
+    #[error("Git error: {0}")]
+    Git(#[from] git2::Error),
+}
+
+/// Git hooks baouncer knows how to install.
+#[derive(Debug, Clone, Copy)]
+pub enum Hook {
+    CommitMsg,
+    /// Runs the commit wizard when a plain `git commit` leaves the message to be written.
+    PrepareCommitMsg,
+}
+impl Hook {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hook::CommitMsg => "commit-msg",
+            Hook::PrepareCommitMsg => "prepare-commit-msg",
+        }
+    }
+
+    fn command(&self) -> &'static str {
+        match self {
+            Hook::CommitMsg => r#"exec baouncer check-commit "$1""#,
+            // git gives hooks no stdin, so the wizard reads from the terminal when there is one
+            Hook::PrepareCommitMsg => {
+                r#"if [ -z "$2" ] && (exec </dev/tty) 2>/dev/null; then
+    exec baouncer prepare-commit-msg "$@" </dev/tty
+fi"#
+            }
+        }
+    }
+
+    fn script(&self) -> String {
+        format!(
+            r#"#!/usr/bin/env sh
+{MARKER}
+# Installed by baouncer. Remove with `baouncer {name}-hook --uninstall`.
+
+hook_dir=$(dirname "$0")
+
+# chain to the hook that was in place before baouncer was installed
+if [ -x "$hook_dir/{name}.{BACKUP_SUFFIX}" ]; then
+    "$hook_dir/{name}.{BACKUP_SUFFIX}" "$@" || exit $?
+fi
+
+{command}
+"#,
+            name = self.name(),
+            command = self.command(),
+        )
+    }
+}
+
+/// Resolves the directory git runs hooks from, honouring `core.hooksPath`.
+///
+/// Hooks live in the common git directory, so linked worktrees and submodules resolve to the
+/// directory git actually uses rather than `<workdir>/.git/hooks`.
+pub fn hooks_dir(repo: &Repository) -> Result<PathBuf, HookError> {
+    let config = repo.config()?;
+
+    match config.get_path("core.hooksPath") {
+        Ok(path) if path.is_absolute() => Ok(path),
+        Ok(path) => {
+            // relative paths are resolved against the root of the working tree, as git does
+            let base = repo.workdir().unwrap_or_else(|| repo.path());
+
+            Ok(base.join(path))
+        }
+        Err(_) => Ok(repo.commondir().join("hooks")),
+    }
+}
+
+fn is_managed(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|contents| contents.contains(MARKER))
+        .unwrap_or(false)
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> HookError + '_ {
+    move |err| HookError::IoError {
+        path: path.to_path_buf(),
+        source: Arc::new(err),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), HookError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(io_error(path))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), HookError> {
+    Ok(())
+}
+
+/// Runs the hook called `name`, as `git commit` would, if the repository has an executable one.
+///
+/// The hook runs from the root of the working tree with stdin closed and its output shown.
+/// `GIT_EDITOR` is set to `:`, as baouncer never opens an editor.
+pub fn run(repo: &Repository, name: &str, args: &[&str]) -> Result<(), HookError> {
+    let path = hooks_dir(repo)?.join(name);
+
+    if !is_executable(&path) {
+        debug!("no executable {} hook at {:?}", name, path);
+        return Ok(());
+    }
+
+    let status = Command::new(&path)
+        .args(args)
+        .current_dir(repo.workdir().unwrap_or_else(|| repo.path()))
+        .env("GIT_INDEX_FILE", repo.path().join("index"))
+        .env("GIT_EDITOR", ":")
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|err| HookError::RunError {
+            path: path.clone(),
+            source: Arc::new(err),
+        })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(HookError::Rejected {
+            hook: name.to_string(),
+            status,
+        })
+    }
+}
+
+/// Installs `hook` into the repository's hooks directory.
+///
+/// An existing hook that baouncer did not write is moved to `<hook>.local` and chained to from
+/// the installed script. If that backup location is already taken, nothing is written.
+pub fn install(repo: &Repository, hook: Hook) -> Result<PathBuf, HookError> {
+    let dir = hooks_dir(repo)?;
+    let path = dir.join(hook.name());
+    let backup = dir.join(format!("{}.{}", hook.name(), BACKUP_SUFFIX));
+
+    fs::create_dir_all(&dir).map_err(io_error(&dir))?;
+
+    if path.exists() && !is_managed(&path) {
+        if backup.exists() {
+            return Err(HookError::BackupExists { hook: path, backup });
+        }
+
+        fs::rename(&path, &backup).map_err(io_error(&path))?;
+
+        println!(
+            "{} {} {}",
+            "moved existing hook to".yellow(),
+            backup.display().to_string().yellow(),
+            "and chained it".yellow()
+        );
+    }
+
+    fs::write(&path, hook.script()).map_err(io_error(&path))?;
+    make_executable(&path)?;
+
+    println!(
+        "{} {} {}",
+        hook.name().yellow(),
+        "hook installed at".yellow(),
+        path.display().to_string().yellow()
+    );
+
+    Ok(path)
+}
+
+/// Removes a hook previously installed by baouncer, restoring any hook it had moved aside.
+pub fn uninstall(repo: &Repository, hook: Hook) -> Result<(), HookError> {
+    let dir = hooks_dir(repo)?;
+    let path = dir.join(hook.name());
+    let backup = dir.join(format!("{}.{}", hook.name(), BACKUP_SUFFIX));
+
+    if path.exists() {
+        if !is_managed(&path) {
+            return Err(HookError::ForeignHook { path });
+        }
+
+        fs::remove_file(&path).map_err(io_error(&path))?;
+
+        println!(
+            "{} {} {}",
+            hook.name().yellow(),
+            "hook removed from".yellow(),
+            path.display().to_string().yellow()
+        );
+    }
+
+    if backup.exists() {
+        fs::rename(&backup, &path).map_err(io_error(&backup))?;
+
+        println!(
+            "{} {}",
+            "restored previous hook at".yellow(),
+            path.display().to_string().yellow()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::init_repo;
+
+    #[test]
+    fn when_installing_over_a_foreign_hook_it_should_back_it_up_and_chain_it() {
+        let (dir, repo) = init_repo("hooks-chain");
+        let hooks = hooks_dir(&repo).unwrap();
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(hooks.join("commit-msg"), "#!/bin/sh\nexit 0\n").unwrap();
+
+        install(&repo, Hook::CommitMsg).unwrap();
+
+        assert!(is_managed(&hooks.join("commit-msg")));
+        assert_eq!(
+            fs::read_to_string(hooks.join("commit-msg.local")).unwrap(),
+            "#!/bin/sh\nexit 0\n"
+        );
+
+        uninstall(&repo, Hook::CommitMsg).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(hooks.join("commit-msg")).unwrap(),
+            "#!/bin/sh\nexit 0\n"
+        );
+        assert!(!hooks.join("commit-msg.local").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_installing_the_prepare_commit_msg_hook_it_should_only_prompt_for_blank_messages() {
+        let (dir, repo) = init_repo("hooks-prepare");
+
+        let path = install(&repo, Hook::PrepareCommitMsg).unwrap();
+        let script = fs::read_to_string(&path).unwrap();
+
+        assert!(path.ends_with("prepare-commit-msg"));
+        assert!(script.contains(r#"if [ -z "$2" ]"#));
+        assert!(script.contains("`baouncer prepare-commit-msg-hook --uninstall`"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_core_hooks_path_is_relative_it_should_resolve_against_the_workdir() {
+        let (dir, repo) = init_repo("hooks-path");
+        repo.config()
+            .unwrap()
+            .set_str("core.hooksPath", ".githooks")
+            .unwrap();
+
+        let hooks = hooks_dir(&repo).unwrap();
+
+        assert_eq!(hooks, repo.workdir().unwrap().join(".githooks"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn when_running_a_failing_hook_it_should_reject_the_commit() {
+        let (dir, repo) = init_repo("hooks-run");
+        let hooks = hooks_dir(&repo).unwrap();
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(hooks.join("pre-commit"), "#!/bin/sh\nexit 3\n").unwrap();
+
+        // hooks that are not executable are skipped, as git does
+        assert!(run(&repo, "pre-commit", &[]).is_ok());
+
+        make_executable(&hooks.join("pre-commit")).unwrap();
+
+        assert!(matches!(
+            run(&repo, "pre-commit", &[]),
+            Err(HookError::Rejected { status, .. }) if status.code() == Some(3)
+        ));
+        assert!(run(&repo, "post-commit", &[]).is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_uninstalling_a_foreign_hook_it_should_refuse() {
+        let (dir, repo) = init_repo("hooks-foreign");
+        let hooks = hooks_dir(&repo).unwrap();
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(hooks.join("commit-msg"), "#!/bin/sh\nexit 0\n").unwrap();
+
+        let result = uninstall(&repo, Hook::CommitMsg);
+
+        assert!(matches!(result, Err(HookError::ForeignHook { .. })));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    config::{Config, Scope as ConfigScope},
+    git,
+    workspace::{self, Member},
+};
+use git2::{Pathspec, PathspecFlags, Repository};
+use std::path::{Path, PathBuf};
+
+/// The scopes touched by a set of paths, most touched first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inference {
+    pub scopes: Vec<(String, usize)>,
+}
+impl Inference {
+    /// The most likely scope, i.e. the one covering the most paths.
+    pub fn best(&self) -> Option<&str> {
+        self.scopes.first().map(|(name, _)| name.as_str())
+    }
+
+    /// Whether the paths span more than one scope.
+    pub fn is_ambiguous(&self) -> bool {
+        self.scopes.len() > 1
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.scopes.iter().map(|(name, _)| name.as_str()).collect()
+    }
+}
+
+/// Maps paths to scopes through the `paths` globs of the scope catalogue or, when no scope has
+/// any, through the crates of the Cargo workspace.
+pub enum ScopeMap {
+    Globs(Vec<(String, Pathspec)>),
+    Workspace(Vec<Member>),
+}
+impl ScopeMap {
+    pub fn new(catalogue: &[ConfigScope], members: Vec<Member>) -> Result<Self, git2::Error> {
+        let mut globs = vec![];
+
+        for scope in catalogue.iter().filter(|scope| !scope.paths.is_empty()) {
+            globs.push((scope.name.clone(), Pathspec::new(&scope.paths)?));
+        }
+
+        if globs.is_empty() {
+            Ok(ScopeMap::Workspace(members))
+        } else {
+            Ok(ScopeMap::Globs(globs))
+        }
+    }
+
+    /// The scope `path` belongs to; the first matching catalogue entry wins.
+    pub fn scope_of(&self, path: &Path) -> Option<&str> {
+        match self {
+            ScopeMap::Globs(globs) => globs
+                .iter()
+                .find(|(_, pathspec)| pathspec.matches_path(path, PathspecFlags::DEFAULT))
+                .map(|(name, _)| name.as_str()),
+            ScopeMap::Workspace(members) => {
+                workspace::member_of(members, path).map(|member| member.name.as_str())
+            }
+        }
+    }
+
+    /// Counts the paths per scope, ignoring paths no scope covers.
+    pub fn infer(&self, paths: &[PathBuf]) -> Inference {
+        let mut scopes: Vec<(String, usize)> = vec![];
+
+        for scope in paths.iter().filter_map(|path| self.scope_of(path)) {
+            match scopes.iter_mut().find(|(name, _)| name == scope) {
+                Some((_, count)) => *count += 1,
+                None => scopes.push((scope.to_string(), 1)),
+            }
+        }
+
+        // stable, so ties keep the order in which scopes were first seen
+        scopes.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+        Inference { scopes }
+    }
+}
+
+/// Infers the scope of the changes staged in `repo`.
+pub fn staged(repo: &Repository, cfg: &Config) -> Result<Inference, git2::Error> {
+    let members = repo.workdir().map(workspace::members).unwrap_or_default();
+    let map = ScopeMap::new(&cfg.scopes, members)?;
+
+    Ok(map.infer(&git::staged_paths(repo)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn when_scopes_have_globs_it_should_count_matching_paths() {
+        let catalogue = vec![
+            ConfigScope {
+                name: "parser".to_string(),
+                description: "The parser".to_string(),
+                paths: vec!["cc-scanner/**".to_string()],
+            },
+            ConfigScope {
+                name: "config".to_string(),
+                description: "Configuration".to_string(),
+                paths: vec!["src/config.rs".to_string()],
+            },
+        ];
+        let map = ScopeMap::new(&catalogue, vec![]).unwrap();
+
+        let inference = map.infer(&paths(&[
+            "src/config.rs",
+            "cc-scanner/src/lib.rs",
+            "cc-scanner/src/grammar.pest",
+            "README.md",
+        ]));
+
+        assert_eq!(inference.best(), Some("parser"));
+        assert_eq!(inference.names(), vec!["parser", "config"]);
+        assert!(inference.is_ambiguous());
+    }
+
+    #[test]
+    fn when_no_scope_has_globs_it_should_use_workspace_members() {
+        let members = vec![
+            Member {
+                name: "cc-scanner".to_string(),
+                path: PathBuf::from("cc-scanner"),
+                version: None,
+            },
+            Member {
+                name: "baouncer".to_string(),
+                path: PathBuf::new(),
+                version: None,
+            },
+        ];
+        let map = ScopeMap::new(&[], members).unwrap();
+
+        let inference = map.infer(&paths(&["cc-scanner/src/lib.rs"]));
+
+        assert_eq!(inference.best(), Some("cc-scanner"));
+        assert!(!inference.is_ambiguous());
+    }
+}
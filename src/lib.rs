@@ -9,3 +9,34 @@ pub mod prompt;
 pub mod gh_cli;
 
 pub mod git;
+
+pub mod check;
+
+pub mod hooks;
+
+pub mod report;
+
+pub mod builder;
+
+pub mod bump;
+
+pub mod changelog;
+
+pub mod lint;
+
+pub mod fix;
+
+pub mod suggest;
+
+pub mod workspace;
+
+pub mod infer;
+
+pub mod reword;
+
+pub mod revert;
+
+pub mod sign;
+
+#[cfg(test)]
+mod test_support;
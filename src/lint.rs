@@ -0,0 +1,631 @@
+use crate::suggest::did_you_mean;
+use cc_scanner::{
+    conventional_commit::CommitType,
+    spans::{Span, SpannedCommit},
+};
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+use serde::Deserialize;
+use std::fmt;
+use thiserror::Error;
+
+/// How a rule violation is treated: ignored, reported, or reported and rejected.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Off,
+    Warn,
+    Error,
+}
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self {
+            Severity::Off => "off",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        };
+        write!(f, "{}", severity)
+    }
+}
+
+/// Expected casing of the first character of the commit description.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SubjectCase {
+    LowerCase,
+    SentenceCase,
+}
+
+/// The severity of a rule and its optional argument.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct RuleConfig<T> {
+    pub level: Severity,
+    pub value: Option<T>,
+}
+impl<T> RuleConfig<T> {
+    fn new(level: Severity, value: Option<T>) -> Self {
+        RuleConfig { level, value }
+    }
+}
+
+/// The `[rules]` table of `.baouncer.toml`.
+///
+/// Every rule is optional so configs can be layered; [`RulesConfig::merge`] lets a project
+/// config override individual rules of the global one.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RulesConfig {
+    pub header_max_length: Option<RuleConfig<usize>>,
+    pub subject_case: Option<RuleConfig<SubjectCase>>,
+    pub subject_full_stop: Option<RuleConfig<()>>,
+    pub type_enum: Option<RuleConfig<Vec<String>>>,
+    pub type_typo: Option<RuleConfig<()>>,
+    pub scope_enum: Option<RuleConfig<Vec<String>>>,
+    pub scope_typo: Option<RuleConfig<()>>,
+    pub scope_required: Option<RuleConfig<()>>,
+    pub body_max_line_length: Option<RuleConfig<usize>>,
+    pub footer_token_enum: Option<RuleConfig<Vec<String>>>,
+}
+impl RulesConfig {
+    /// Rules applied when no configuration overrides them.
+    pub fn defaults() -> Self {
+        RulesConfig {
+            header_max_length: Some(RuleConfig::new(Severity::Warn, Some(72))),
+            subject_case: Some(RuleConfig::new(Severity::Off, Some(SubjectCase::LowerCase))),
+            subject_full_stop: Some(RuleConfig::new(Severity::Warn, None)),
+            type_enum: Some(RuleConfig::new(Severity::Off, None)),
+            type_typo: Some(RuleConfig::new(Severity::Warn, None)),
+            scope_enum: Some(RuleConfig::new(Severity::Off, None)),
+            scope_typo: Some(RuleConfig::new(Severity::Warn, None)),
+            scope_required: Some(RuleConfig::new(Severity::Off, None)),
+            body_max_line_length: Some(RuleConfig::new(Severity::Off, Some(100))),
+            footer_token_enum: Some(RuleConfig::new(Severity::Off, None)),
+        }
+    }
+
+    /// Overrides every rule set in `other`.
+    pub fn merge(&mut self, other: RulesConfig) {
+        fn merge_rule<T>(base: &mut Option<RuleConfig<T>>, other: Option<RuleConfig<T>>) {
+            if let Some(mut other) = other {
+                // keep the existing argument when only the level is overridden
+                if other.value.is_none() {
+                    other.value = base.take().and_then(|base| base.value);
+                }
+
+                *base = Some(other);
+            }
+        }
+
+        merge_rule(&mut self.header_max_length, other.header_max_length);
+        merge_rule(&mut self.subject_case, other.subject_case);
+        merge_rule(&mut self.subject_full_stop, other.subject_full_stop);
+        merge_rule(&mut self.type_enum, other.type_enum);
+        merge_rule(&mut self.type_typo, other.type_typo);
+        merge_rule(&mut self.scope_enum, other.scope_enum);
+        merge_rule(&mut self.scope_typo, other.scope_typo);
+        merge_rule(&mut self.scope_required, other.scope_required);
+        merge_rule(&mut self.body_max_line_length, other.body_max_line_length);
+        merge_rule(&mut self.footer_token_enum, other.footer_token_enum);
+    }
+}
+
+/// A single rule violation, pointing at the offending text when possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>,
+    pub help: Option<String>,
+}
+
+/// A violation paired with the message it was found in, renderable as a miette diagnostic.
+#[derive(Debug, Error)]
+#[error("{}", violation.message)]
+pub struct LintDiagnostic {
+    pub violation: Violation,
+    pub message: String,
+}
+impl Diagnostic for LintDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.violation.rule))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        match self.violation.severity {
+            Severity::Error => Some(miette::Severity::Error),
+            _ => Some(miette::Severity::Warning),
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.violation
+            .help
+            .as_ref()
+            .map(|help| Box::new(help) as Box<dyn fmt::Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.message)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let span = self.violation.span.clone()?;
+
+        Some(Box::new(std::iter::once(LabeledSpan::at(
+            span,
+            self.violation.rule,
+        ))))
+    }
+}
+
+/// Returns the 1-based line and column of the byte `offset` in `message`.
+pub fn line_col(message: &str, offset: usize) -> (usize, usize) {
+    let before = &message[..offset.min(message.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+
+    (line, column)
+}
+
+/// Help text for a value missing from an allow-list: the closest allowed value if there is
+/// one, otherwise the whole list.
+fn allowed_help(value: &str, allowed: &[String], what: &str) -> String {
+    match did_you_mean(value, allowed.iter().map(String::as_str)) {
+        Some(suggestion) => format!("did you mean `{}`?", suggestion),
+        None => format!("allowed {} are: {}", what, allowed.join(", ")),
+    }
+}
+
+struct Linter<'a> {
+    message: &'a str,
+    spanned: &'a SpannedCommit,
+    violations: Vec<Violation>,
+}
+impl Linter<'_> {
+    fn report(
+        &mut self,
+        rule: &'static str,
+        severity: Severity,
+        message: String,
+        span: Option<Span>,
+        help: Option<String>,
+    ) {
+        self.violations.push(Violation {
+            rule,
+            severity,
+            message,
+            span,
+            help,
+        });
+    }
+
+    fn header_max_length(&mut self, severity: Severity, max: usize) {
+        let header = &self.message[self.spanned.spans.header.clone()];
+        let length = header.chars().count();
+
+        if length > max {
+            self.report(
+                "header-max-length",
+                severity,
+                format!(
+                    "Header is {} characters long, the maximum is {}",
+                    length, max
+                ),
+                Some(self.spanned.spans.header.clone()),
+                None,
+            );
+        }
+    }
+
+    fn subject_case(&mut self, severity: Severity, case: SubjectCase) {
+        let Some(first) = self.spanned.commit.description.chars().next() else {
+            return;
+        };
+
+        let (matches, expected) = match case {
+            SubjectCase::LowerCase => (!first.is_uppercase(), "lower case"),
+            SubjectCase::SentenceCase => (!first.is_lowercase(), "sentence case"),
+        };
+
+        if !matches {
+            let start = self.spanned.spans.description.start;
+
+            self.report(
+                "subject-case",
+                severity,
+                format!("Description should be in {}", expected),
+                Some(start..start + first.len_utf8()),
+                None,
+            );
+        }
+    }
+
+    fn subject_full_stop(&mut self, severity: Severity) {
+        if self.spanned.commit.description.ends_with('.') {
+            let end = self.spanned.spans.description.end;
+
+            self.report(
+                "subject-full-stop",
+                severity,
+                "Description should not end with a full stop".to_string(),
+                Some(end - 1..end),
+                Some("remove the trailing period".to_string()),
+            );
+        }
+    }
+
+    fn type_enum(&mut self, severity: Severity, allowed: &[String]) {
+        let commit_type = self.spanned.commit.commit_type.as_str();
+
+        if !allowed.iter().any(|allowed| allowed == commit_type) {
+            self.report(
+                "type-enum",
+                severity,
+                format!("Commit type {:?} is not allowed", commit_type),
+                Some(self.spanned.spans.commit_type.clone()),
+                Some(allowed_help(commit_type, allowed, "types")),
+            );
+        }
+    }
+
+    fn type_typo(&mut self, severity: Severity, known: &[String]) {
+        // types the parser recognises (`doc`, `perf`, ...) are not typos even when the
+        // configuration names them differently (`docs`)
+        if !matches!(self.spanned.commit.commit_type, CommitType::Custom(_)) {
+            return;
+        }
+
+        let commit_type = self.spanned.commit.commit_type.as_str();
+
+        if known.iter().any(|known| known == commit_type) {
+            return;
+        }
+
+        if let Some(suggestion) = did_you_mean(commit_type, known.iter().map(String::as_str)) {
+            self.report(
+                "type-typo",
+                severity,
+                format!("Unknown commit type {:?}", commit_type),
+                Some(self.spanned.spans.commit_type.clone()),
+                Some(format!("did you mean `{}`?", suggestion)),
+            );
+        }
+    }
+
+    fn scope_enum(&mut self, severity: Severity, allowed: &[String]) {
+        let (Some(scope), Some(span)) = (&self.spanned.commit.scope, &self.spanned.spans.scope)
+        else {
+            return;
+        };
+
+        if !allowed.contains(&scope.noun) {
+            self.report(
+                "scope-enum",
+                severity,
+                format!("Scope {:?} is not allowed", scope.noun),
+                Some(span.clone()),
+                Some(allowed_help(&scope.noun, allowed, "scopes")),
+            );
+        }
+    }
+
+    fn scope_typo(&mut self, severity: Severity, known: &[String]) {
+        let (Some(scope), Some(span)) = (&self.spanned.commit.scope, &self.spanned.spans.scope)
+        else {
+            return;
+        };
+
+        if known.contains(&scope.noun) {
+            return;
+        }
+
+        if let Some(suggestion) = did_you_mean(&scope.noun, known.iter().map(String::as_str)) {
+            self.report(
+                "scope-typo",
+                severity,
+                format!("Unknown scope {:?}", scope.noun),
+                Some(span.clone()),
+                Some(format!("did you mean `{}`?", suggestion)),
+            );
+        }
+    }
+
+    fn scope_required(&mut self, severity: Severity) {
+        if self.spanned.commit.scope.is_none() {
+            self.report(
+                "scope-required",
+                severity,
+                "Commit must have a scope".to_string(),
+                Some(self.spanned.spans.commit_type.clone()),
+                Some("add a scope after the type, e.g. feat(cli): ...".to_string()),
+            );
+        }
+    }
+
+    fn body_max_line_length(&mut self, severity: Severity, max: usize) {
+        let Some(body) = self.spanned.spans.body.clone() else {
+            return;
+        };
+
+        let mut offset = body.start;
+
+        for line in self.message[body].split('\n') {
+            let length = line.chars().count();
+
+            if length > max {
+                self.report(
+                    "body-max-line-length",
+                    severity,
+                    format!(
+                        "Body line is {} characters long, the maximum is {}",
+                        length, max
+                    ),
+                    Some(offset..offset + line.len()),
+                    None,
+                );
+            }
+
+            offset += line.len() + 1;
+        }
+    }
+
+    fn footer_token_enum(&mut self, severity: Severity, allowed: &[String]) {
+        let spanned = self.spanned;
+
+        for (footer, spans) in spanned.commit.footers.iter().zip(&spanned.spans.footers) {
+            if footer.is_breaking_change() || allowed.contains(&footer.token) {
+                continue;
+            }
+
+            self.report(
+                "footer-token-enum",
+                severity,
+                format!("Footer token {:?} is not allowed", footer.token),
+                Some(spans.token.clone()),
+                Some(format!("allowed footer tokens are: {}", allowed.join(", "))),
+            );
+        }
+    }
+}
+
+/// Runs every enabled rule against a parsed commit message.
+///
+/// `commit_types` is the allow-list used by `type-enum` when the rule has no explicit value.
+pub fn lint(
+    message: &str,
+    spanned: &SpannedCommit,
+    rules: &RulesConfig,
+    commit_types: &[String],
+) -> Vec<Violation> {
+    let mut linter = Linter {
+        message,
+        spanned,
+        violations: vec![],
+    };
+
+    fn enabled<T>(rule: &Option<RuleConfig<T>>) -> Option<(Severity, Option<&T>)> {
+        rule.as_ref()
+            .filter(|rule| rule.level != Severity::Off)
+            .map(|rule| (rule.level, rule.value.as_ref()))
+    }
+
+    if let Some((severity, max)) = enabled(&rules.header_max_length) {
+        linter.header_max_length(severity, max.copied().unwrap_or(72));
+    }
+    if let Some((severity, case)) = enabled(&rules.subject_case) {
+        linter.subject_case(severity, case.copied().unwrap_or(SubjectCase::LowerCase));
+    }
+    if let Some((severity, _)) = enabled(&rules.subject_full_stop) {
+        linter.subject_full_stop(severity);
+    }
+    // the typo rules only matter when the stricter enum rules are not already reporting
+    if let Some((severity, allowed)) = enabled(&rules.type_enum) {
+        linter.type_enum(severity, allowed.map(Vec::as_slice).unwrap_or(commit_types));
+    } else if let Some((severity, _)) = enabled(&rules.type_typo) {
+        linter.type_typo(severity, commit_types);
+    }
+    if let Some((severity, Some(allowed))) = enabled(&rules.scope_enum) {
+        linter.scope_enum(severity, allowed);
+    } else if let Some((severity, _)) = enabled(&rules.scope_typo) {
+        let known = rules
+            .scope_enum
+            .as_ref()
+            .and_then(|rule| rule.value.as_deref())
+            .unwrap_or_default();
+
+        linter.scope_typo(severity, known);
+    }
+    if let Some((severity, _)) = enabled(&rules.scope_required) {
+        linter.scope_required(severity);
+    }
+    if let Some((severity, max)) = enabled(&rules.body_max_line_length) {
+        linter.body_max_line_length(severity, max.copied().unwrap_or(100));
+    }
+    if let Some((severity, Some(allowed))) = enabled(&rules.footer_token_enum) {
+        linter.footer_token_enum(severity, allowed);
+    }
+
+    linter.violations
+}
+
+/// Whether any violation should cause the message to be rejected.
+pub fn has_errors(violations: &[Violation]) -> bool {
+    violations
+        .iter()
+        .any(|violation| violation.severity == Severity::Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cc_scanner::parse_commit_with_spans;
+
+    fn run(message: &str, rules: RulesConfig) -> Vec<Violation> {
+        let spanned = parse_commit_with_spans(message).unwrap();
+
+        lint(
+            message,
+            &spanned,
+            &rules,
+            &["feat".to_string(), "fix".to_string()],
+        )
+    }
+
+    fn error<T>(value: Option<T>) -> Option<RuleConfig<T>> {
+        Some(RuleConfig::new(Severity::Error, value))
+    }
+
+    #[test]
+    fn when_no_rules_are_enabled_it_should_report_nothing() {
+        assert!(run("Feat: Anything goes.", RulesConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn when_the_header_is_too_long_it_should_point_at_the_header() {
+        let rules = RulesConfig {
+            header_max_length: error(Some(10)),
+            ..RulesConfig::default()
+        };
+
+        let violations = run("feat: a long header", rules);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "header-max-length");
+        assert_eq!(violations[0].span, Some(0..19));
+    }
+
+    #[test]
+    fn when_the_description_has_a_trailing_period_it_should_point_at_it() {
+        let rules = RulesConfig {
+            subject_full_stop: error(None),
+            ..RulesConfig::default()
+        };
+
+        let violations = run("fix: a bug.", rules);
+
+        assert_eq!(violations[0].span, Some(10..11));
+        assert!(has_errors(&violations));
+    }
+
+    #[test]
+    fn when_the_type_is_not_allowed_it_should_fall_back_to_configured_types() {
+        let rules = RulesConfig {
+            type_enum: error(None),
+            ..RulesConfig::default()
+        };
+
+        assert!(run("fix: a bug", rules.clone()).is_empty());
+        assert_eq!(run("fxi: a bug", rules)[0].rule, "type-enum");
+    }
+
+    #[test]
+    fn when_the_type_or_scope_looks_like_a_typo_it_should_suggest_the_known_one() {
+        let rules = RulesConfig {
+            type_typo: error(None),
+            scope_enum: Some(RuleConfig::new(
+                Severity::Off,
+                Some(vec!["parser".to_string()]),
+            )),
+            scope_typo: error(None),
+            ..RulesConfig::default()
+        };
+
+        let violations = run("fxi(parsre): a bug", rules.clone());
+
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].help.as_deref(), Some("did you mean `fix`?"));
+        assert_eq!(
+            violations[1].help.as_deref(),
+            Some("did you mean `parser`?")
+        );
+
+        // unrelated custom types and scopes are not typos
+        assert!(run("release(docs): 1.0", rules).is_empty());
+    }
+
+    #[test]
+    fn when_the_type_is_one_the_parser_knows_it_should_not_be_a_typo() {
+        let rules = RulesConfig {
+            type_typo: error(None),
+            ..RulesConfig::default()
+        };
+        let known = ["docs".to_string()];
+        let lint_type = |message: &str| {
+            lint(
+                message,
+                &parse_commit_with_spans(message).unwrap(),
+                &rules,
+                &known,
+            )
+        };
+
+        assert!(lint_type("doc: fix a typo").is_empty());
+        assert_eq!(
+            lint_type("dosc: fix a typo")[0].help.as_deref(),
+            Some("did you mean `docs`?")
+        );
+    }
+
+    #[test]
+    fn when_scopes_are_restricted_it_should_reject_unknown_and_missing_scopes() {
+        let rules = RulesConfig {
+            scope_enum: error(Some(vec!["cli".to_string()])),
+            scope_required: Some(RuleConfig::new(Severity::Warn, None)),
+            ..RulesConfig::default()
+        };
+
+        assert!(run("fix(cli): a bug", rules.clone()).is_empty());
+        assert_eq!(run("fix(api): a bug", rules.clone())[0].span, Some(4..7));
+
+        let violations = run("fix: a bug", rules);
+
+        assert_eq!(violations[0].rule, "scope-required");
+        assert!(!has_errors(&violations));
+    }
+
+    #[test]
+    fn when_checking_the_body_and_footers_it_should_point_at_offending_lines_and_tokens() {
+        let rules = RulesConfig {
+            body_max_line_length: error(Some(5)),
+            footer_token_enum: error(Some(vec!["Refs".to_string()])),
+            subject_case: error(Some(SubjectCase::SentenceCase)),
+            ..RulesConfig::default()
+        };
+        let message = "feat: Add\n\nshort\nway too long\n\nRefs: #1\nAcked-by: me";
+
+        let violations = run(message, rules);
+        let rules: Vec<&str> = violations.iter().map(|v| v.rule).collect();
+
+        assert_eq!(rules, vec!["body-max-line-length", "footer-token-enum"]);
+        assert_eq!(
+            &message[violations[0].span.clone().unwrap()],
+            "way too long"
+        );
+        assert_eq!(&message[violations[1].span.clone().unwrap()], "Acked-by");
+    }
+
+    #[test]
+    fn when_merging_rules_it_should_keep_the_value_when_only_the_level_changes() {
+        let mut rules = RulesConfig::defaults();
+
+        rules.merge(RulesConfig {
+            header_max_length: error(None),
+            ..RulesConfig::default()
+        });
+
+        assert_eq!(rules.header_max_length, error(Some(72)));
+    }
+
+    #[test]
+    fn when_computing_line_and_column_it_should_be_one_based() {
+        assert_eq!(line_col("feat: a\n\nbody", 0), (1, 1));
+        assert_eq!(line_col("feat: a\n\nbody", 11), (3, 3));
+    }
+}
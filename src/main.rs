@@ -1,14 +1,113 @@
 use baouncer::{
-    command_line,
-    config::{self, ConfigArgs, ConfigPrompt},
-    git, logger,
-    prompt::{
-        body, breaking_change, commit_type, confirm_commit, footers, issues, scope, subject,
-        Prompts,
-    },
+    builder::{self, CommitArgs},
+    bump::{self, Bump},
+    changelog, check, command_line,
+    config::{self, Config, ConfigArgs},
+    fix,
+    git::{self, CommitOptions},
+    hooks::{self, Hook},
+    logger,
+    prompt::{self, confirm_commit},
+    report::Format,
+    revert, reword,
+    workspace::{self, Member},
 };
-use cc_scanner::{conventional_commit::ConventionalCommit, parse_commit};
+use cc_scanner::{autosquash::Autosquash, conventional_commit::ConventionalCommit, parse_commit};
+use clap::ArgMatches;
+use colored::Colorize;
+use git2::Repository;
+use log::info;
 use miette::{miette, Result};
+use std::{fs, path::Path};
+
+/// Number of commits offered when picking the target of `commit --fixup`.
+const RECENT_COMMITS: usize = 20;
+
+fn format(matches: &ArgMatches) -> Format {
+    Format::from(
+        matches
+            .get_one::<String>("format")
+            .map(String::as_str)
+            .unwrap_or("text"),
+    )
+}
+
+/// The `--sign`/`--no-sign` override, if any.
+fn sign(matches: &ArgMatches) -> Option<bool> {
+    if matches.get_flag("sign") {
+        Some(true)
+    } else if matches.get_flag("no_sign") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// The options of the `commit` subcommand that affect how the commit is created.
+fn commit_options(matches: &ArgMatches) -> CommitOptions {
+    CommitOptions {
+        sign: sign(matches),
+        allow_empty: matches.get_flag("allow_empty"),
+        no_verify: matches.get_flag("no_verify"),
+    }
+}
+
+/// Makes sure there is something to commit before prompting for the message, offering to stage
+/// the changes to tracked files when nothing is staged.
+fn ensure_staged(repo: &Repository, allow_empty: bool) -> Result<()> {
+    if allow_empty {
+        return Ok(());
+    }
+
+    let status = git::status(repo).map_err(|err| miette!("{}", err))?;
+
+    if status.has_staged() {
+        return Ok(());
+    }
+
+    if !status.unstaged.is_empty()
+        && builder::is_interactive()
+        && prompt::stage_tracked(&status.unstaged)?
+    {
+        return git::stage_tracked(repo).map_err(|err| miette!("{}", err));
+    }
+
+    Err(miette!("{}", git::nothing_staged(status)))
+}
+
+/// Lists the crates of the Cargo workspace at the root of `repo`.
+fn workspace_members(repo: &Repository) -> Result<Vec<Member>> {
+    let members = repo.workdir().map(workspace::members).unwrap_or_default();
+
+    if members.is_empty() {
+        return Err(miette!(
+            "--workspace requires a Cargo workspace at the repository root"
+        ));
+    }
+
+    Ok(members)
+}
+
+/// Parses an existing message to use as defaults when rewriting a commit, applying the fixes
+/// of `check-commit --fix` first.
+///
+/// A message that is still not a conventional commit yields no defaults, so the commit is
+/// rebuilt from scratch.
+fn message_defaults(message: &str, cfg: &Config) -> Option<ConventionalCommit> {
+    let fixed = fix::fix(&check::cleanup_message(message), cfg);
+
+    match parse_commit(&fixed.message) {
+        Ok(commit) => Some(commit),
+        Err(err) => {
+            eprintln!(
+                "{} {}",
+                "not a conventional commit, starting from scratch:".yellow(),
+                err.kind
+            );
+            None
+        }
+    }
+}
 
 fn main() -> Result<()> {
     // initialize command line interface
@@ -21,7 +120,7 @@ fn main() -> Result<()> {
     logger::init(matches.get_flag("debug"), matches.get_flag("verbose"));
 
     // initialize cli config
-    let cfg = config::init(ConfigArgs {
+    let mut cfg = config::init(ConfigArgs {
         conventional_types: matches.get_flag("conventional_types"),
         scope: matches.get_flag("scope"),
         body: matches.get_flag("body"),
@@ -31,62 +130,416 @@ fn main() -> Result<()> {
     })
     .map_err(|err| miette!("{}", err))?;
 
+    // crates of a Cargo workspace are valid scopes
+    if let Some(root) = git::discover()
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_owned))
+    {
+        cfg.add_workspace_members(&workspace::members(&root));
+    }
+
     // match on subcommand
     match matches.subcommand() {
-        Some(("commit", _)) => {
-            let mut sorted_prompts: Vec<ConfigPrompt> = cfg.prompts.values().cloned().collect();
+        Some(("commit", sub_matches)) if sub_matches.contains_id("fixup") => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+            let rev = sub_matches
+                .get_one::<String>("fixup")
+                .expect("--fixup is present");
 
-            sorted_prompts.sort_by_key(|prompt| prompt.order);
+            let target = if !rev.is_empty() {
+                repo.revparse_single(rev)
+                    .and_then(|object| object.peel_to_commit())
+                    .map_err(|err| miette!("{}", err))?
+                    .id()
+            } else if builder::is_interactive() {
+                let commits =
+                    git::recent_commits(&repo, RECENT_COMMITS).map_err(|err| miette!("{}", err))?;
 
-            let mut commit = ConventionalCommit::default();
+                prompt::pick_commit("Select the commit to fix up", commits)?
+            } else {
+                return Err(miette!(
+                    "--fixup needs a revision when stdin is not a terminal"
+                ));
+            };
 
-            for prompt in sorted_prompts {
-                match prompt.kind {
-                    Prompts::Type => {
-                        commit.set_commit_type(commit_type(cfg.commit_types.clone())?);
-                    }
-                    Prompts::Scope => {
-                        if let Some(choice) = scope()? {
-                            commit.set_scope(choice);
-                        }
-                    }
-                    Prompts::Subject => {
-                        commit.set_description(subject()?);
-                    }
-                    Prompts::Body => {
-                        let choice = body()?;
+            let summary = repo
+                .find_commit(target)
+                .map_err(|err| miette!("{}", err))?
+                .summary()
+                .unwrap_or_default()
+                .to_string();
+
+            ensure_staged(&repo, sub_matches.get_flag("allow_empty"))?;
+
+            git::pre_commit(&repo, commit_options(sub_matches))
+                .map_err(|err| miette!("{}", err))?;
+
+            git::commit_message(
+                &format!("{}{}", Autosquash::Fixup.prefix(), summary),
+                commit_options(sub_matches),
+            )
+            .map_err(|err| miette!("{}", err))?;
+        }
+        Some(("commit", sub_matches)) => {
+            let args = CommitArgs {
+                commit_type: sub_matches.get_one::<String>("type").cloned(),
+                scope: sub_matches.get_one::<String>("scope-name").cloned(),
+                subject: sub_matches.get_one::<String>("subject").cloned(),
+                body: sub_matches.get_one::<String>("body-text").cloned(),
+                breaking: sub_matches.get_flag("breaking"),
+                footers: sub_matches
+                    .get_many::<String>("footer")
+                    .map(|values| values.cloned().collect())
+                    .unwrap_or_default(),
+                yes: sub_matches.get_flag("yes"),
+            };
+
+            let amend = sub_matches.get_flag("amend");
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+            let defaults = if amend {
+                let message = git::head_message(&repo).map_err(|err| miette!("{}", err))?;
+
+                message_defaults(&message, &cfg)
+            } else {
+                ensure_staged(&repo, sub_matches.get_flag("allow_empty"))?;
+
+                None
+            };
+
+            git::pre_commit(&repo, commit_options(sub_matches))
+                .map_err(|err| miette!("{}", err))?;
+
+            let mut commit = if args.is_non_interactive() {
+                builder::from_args(&cfg, &args, defaults.as_ref())?
+            } else {
+                builder::from_prompts(&cfg, defaults.as_ref())?
+            };
+
+            // validate commit
+            let parsed_commit = builder::validate(&mut commit)?;
+
+            check::lint_commit(Some(&repo), &commit.as_str(), &cfg)?;
+
+            let write = |commit| {
+                if amend {
+                    git::amend(commit, commit_options(sub_matches))
+                } else {
+                    git::commit(commit, commit_options(sub_matches))
+                }
+                .map_err(|err| miette!("{}", err))
+            };
 
-                        if !choice.is_empty() {
-                            commit.set_body(choice);
+            if args.yes || !builder::is_interactive() {
+                write(commit)?;
+            } else {
+                // display commit message and prompt user to write commit or abort
+                match confirm_commit(parsed_commit) {
+                    Ok(choice) => {
+                        if choice {
+                            write(commit)?;
                         }
                     }
-                    Prompts::IsBreaking => {
-                        commit.set_breaking_change(breaking_change()?);
-                    }
-                    Prompts::Issues => {
-                        commit.set_footers(issues()?);
-                    }
-                    Prompts::Footers => {
-                        if let Some(choice) = footers()? {
-                            commit.set_footers(choice);
-                        }
+                    Err(error) => {
+                        eprintln!("{}", error)
                     }
                 }
             }
+        }
+        Some(("check-commit", sub_matches)) => {
+            // the commit-msg hook runs in a repository, where a revert can be checked against it
+            let repo = git::discover().ok();
+            let message = check::read_message(
+                sub_matches.get_one::<String>("message"),
+                sub_matches.get_one::<String>("FILE"),
+            )
+            .map_err(|err| miette!("{}", err))?;
 
-            // validate commit
-            let parsed_commit = parse_commit(&commit.as_str()).map_err(|err| miette!("{}", err))?;
+            let fixed = fix::fix(&check::cleanup_message(&message), &cfg);
+
+            if !sub_matches.get_flag("fix") {
+                let result = check::check_messages(
+                    repo.as_ref(),
+                    vec![(None, message)],
+                    format(sub_matches),
+                    &cfg,
+                );
+
+                if result.is_err() && fixed.is_changed() {
+                    eprintln!(
+                        "{}\n\n{}\n\n{}",
+                        "suggested message:".yellow(),
+                        fixed.message,
+                        "run with --fix to apply it".yellow()
+                    );
+                }
 
-            // display commit message and prompt user to write commit or abort
-            match confirm_commit(parsed_commit) {
-                Ok(choice) => {
-                    if choice {
-                        git::commit(commit).map_err(|err| miette!("{}", err))?;
+                return result;
+            }
+
+            // --fix requires FILE, so the message came from the commit message file
+            let path = sub_matches
+                .get_one::<String>("FILE")
+                .expect("--fix requires FILE");
+
+            if path == "-" {
+                return Err(miette!(
+                    "--fix cannot rewrite stdin, pass the message file instead"
+                ));
+            }
+
+            if fixed.is_changed() {
+                fs::write(path, fix::rewrite(&message, &fixed.message))
+                    .map_err(|err| miette!("{}", err))?;
+
+                for description in &fixed.fixes {
+                    eprintln!("{} {}", "fixed:".yellow(), description);
+                }
+            }
+
+            check::check_messages(
+                repo.as_ref(),
+                vec![(None, fixed.message)],
+                format(sub_matches),
+                &cfg,
+            )?;
+        }
+        Some(("reword", sub_matches)) => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+            let rev = sub_matches
+                .get_one::<String>("REV")
+                .expect("REV is required");
+            let target = repo
+                .revparse_single(rev)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|err| miette!("{}", err))?;
+
+            // refuse before prompting if the history cannot be replayed
+            reword::descendants(&repo, target.id()).map_err(|err| miette!("{}", err))?;
+
+            let message = String::from_utf8_lossy(target.message_bytes()).to_string();
+            let defaults = message_defaults(&message, &cfg);
+            let mut commit = builder::from_prompts(&cfg, defaults.as_ref())?;
+
+            let parsed_commit = builder::validate(&mut commit)?;
+
+            check::lint_commit(Some(&repo), &commit.as_str(), &cfg)?;
+
+            if confirm_commit(parsed_commit).map_err(|err| miette!("{}", err))? {
+                let tip = reword::reword(&repo, target.id(), &commit.as_str(), sign(sub_matches))
+                    .map_err(|err| miette!("{}", err))?;
+
+                println!(
+                    "{} {} {} {}",
+                    "commit".yellow(),
+                    target.id().to_string().yellow(),
+                    "reworded, new HEAD".yellow(),
+                    tip.to_string().yellow()
+                );
+            }
+        }
+        Some(("revert", sub_matches)) => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+            let rev = sub_matches
+                .get_one::<String>("REV")
+                .expect("REV is required");
+            let target = repo
+                .revparse_single(rev)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|err| miette!("{}", err))?;
+
+            let mut commit = revert::message(&target);
+            let parsed_commit = builder::validate(&mut commit)?;
+
+            check::lint_commit(Some(&repo), &commit.as_str(), &cfg)?;
+
+            if !sub_matches.get_flag("yes")
+                && builder::is_interactive()
+                && !confirm_commit(parsed_commit).map_err(|err| miette!("{}", err))?
+            {
+                return Ok(());
+            }
+
+            revert::apply(&repo, &target, &commit.as_str()).map_err(|err| miette!("{}", err))?;
+
+            let options = CommitOptions {
+                sign: sign(sub_matches),
+                no_verify: sub_matches.get_flag("no_verify"),
+                ..CommitOptions::default()
+            };
+
+            // pre-commit sees the reverted index, as when committing after `git revert --no-commit`
+            let committed =
+                git::pre_commit(&repo, options).and_then(|()| git::commit(commit, options));
+
+            if let Err(err) = committed {
+                // leave the repository as it was rather than half reverted
+                return Err(match revert::abort(&repo) {
+                    Ok(()) => miette!("{}", err),
+                    Err(abort_err) => miette!(
+                        "{}\n{}: {}, run `git reset --merge` to undo the revert",
+                        err,
+                        "could not undo the revert".yellow(),
+                        abort_err
+                    ),
+                });
+            }
+
+            repo.cleanup_state().map_err(|err| miette!("{}", err))?;
+        }
+        Some(("commit-msg-hook", sub_matches)) => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+
+            if sub_matches.get_flag("uninstall") {
+                hooks::uninstall(&repo, Hook::CommitMsg).map_err(|err| miette!("{}", err))?;
+            } else {
+                hooks::install(&repo, Hook::CommitMsg).map_err(|err| miette!("{}", err))?;
+            }
+        }
+        Some(("prepare-commit-msg-hook", sub_matches)) => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+
+            if sub_matches.get_flag("uninstall") {
+                hooks::uninstall(&repo, Hook::PrepareCommitMsg)
+                    .map_err(|err| miette!("{}", err))?;
+            } else {
+                hooks::install(&repo, Hook::PrepareCommitMsg).map_err(|err| miette!("{}", err))?;
+            }
+        }
+        Some(("prepare-commit-msg", sub_matches)) => {
+            let path = sub_matches
+                .get_one::<String>("FILE")
+                .expect("FILE is required");
+            let source = sub_matches.get_one::<String>("SOURCE");
+            let repo = git::discover().ok();
+
+            builder::prepare_message_file(
+                Path::new(path),
+                source.map(String::as_str),
+                builder::is_interactive(),
+                || {
+                    let mut commit = builder::from_prompts(&cfg, None)?;
+
+                    builder::validate(&mut commit)?;
+
+                    check::lint_commit(repo.as_ref(), &commit.as_str(), &cfg)?;
+
+                    Ok(commit.as_str())
+                },
+            )?;
+        }
+        Some(("check-range", sub_matches)) => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+            let range = sub_matches
+                .get_one::<String>("RANGE")
+                .expect("RANGE is required");
+
+            check::check_range(
+                &repo,
+                range,
+                sub_matches.get_flag("include_merges"),
+                format(sub_matches),
+                &cfg,
+            )?;
+        }
+        Some(("bump", sub_matches)) if sub_matches.get_flag("workspace") => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+            let members = workspace_members(&repo)?;
+            let versions =
+                bump::next_crate_versions(&repo, &members).map_err(|err| miette!("{}", err))?;
+
+            for version in versions {
+                let next = &version.next;
+
+                info!(
+                    "{}: {} -> {} ({} bump)",
+                    version.member.name, next.current, next.next, next.bump
+                );
+
+                println!("{} {}", version.member.name, next.next);
+
+                if sub_matches.get_flag("tag") && next.bump != Bump::None {
+                    let tag = next.tag_name(&bump::tag_prefix(&version.member));
+
+                    bump::create_tag(&repo, &tag).map_err(|err| miette!("{}", err))?;
+
+                    eprintln!("{} {} {}", "tag".yellow(), tag.yellow(), "created".yellow());
+                }
+            }
+        }
+        Some(("bump", sub_matches)) => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+            let next = bump::next_version(&repo, "").map_err(|err| miette!("{}", err))?;
+
+            info!("{} -> {} ({} bump)", next.current, next.next, next.bump);
+
+            println!("{}", next.next);
+
+            if sub_matches.get_flag("tag") {
+                if next.bump == Bump::None {
+                    eprintln!("{}", "no releasable changes, not tagging".yellow());
+                } else {
+                    let tag = next.tag_name("");
+
+                    bump::create_tag(&repo, &tag).map_err(|err| miette!("{}", err))?;
+
+                    eprintln!("{} {} {}", "tag".yellow(), tag.yellow(), "created".yellow());
+                }
+            }
+        }
+        Some(("changelog", sub_matches)) => {
+            let repo = git::discover().map_err(|err| miette!("{}", err))?;
+            let from = sub_matches.get_one::<String>("from").map(String::as_str);
+            let to = sub_matches
+                .get_one::<String>("to")
+                .expect("--to has a default");
+
+            let markdown = if sub_matches.get_flag("workspace") {
+                let members = workspace_members(&repo)?;
+                let mut sections = vec![];
+
+                for member in &members {
+                    let release = changelog::crate_release(&repo, &members, member, from, to)
+                        .map_err(|err| miette!("{}", err))?;
+
+                    if !release.commits.is_empty() {
+                        sections.push(changelog::render(&release, &cfg.commit_types));
                     }
                 }
-                Err(error) => {
-                    eprintln!("{}", error)
+
+                sections.join("\n")
+            } else {
+                let release =
+                    changelog::release(&repo, from, to).map_err(|err| miette!("{}", err))?;
+
+                changelog::render(&release, &cfg.commit_types)
+            };
+
+            match sub_matches.get_one::<String>("prepend") {
+                Some(path) => {
+                    changelog::prepend(Path::new(path), &markdown)
+                        .map_err(|err| miette!("{}", err))?;
+
+                    eprintln!("{} {}", "changelog written to".yellow(), path.yellow());
                 }
+                None => print!("{}", markdown),
+            }
+        }
+        Some(("parse", sub_matches)) => {
+            let message = check::read_message(
+                sub_matches.get_one::<String>("message"),
+                sub_matches.get_one::<String>("FILE"),
+            )
+            .map_err(|err| miette!("{}", err))?;
+
+            let commit = check::check_commit(&message)?;
+
+            if sub_matches.get_flag("json") {
+                let json =
+                    serde_json::to_string_pretty(&commit).map_err(|err| miette!("{}", err))?;
+
+                println!("{}", json);
+            } else {
+                println!("{:#?}", commit);
             }
         }
         _ => unreachable!(),
@@ -1,16 +1,20 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, path::PathBuf};
 
 use cc_scanner::{
     conventional_commit::{CommitType, ConventionalCommit, Footer, Scope},
     parse_footers, parse_scope,
 };
 use colored::Colorize;
+use git2::Oid;
 use inquire::{error::InquireResult, required, Confirm, Editor, InquireError, Select, Text};
-use miette::{miette, Result};
+use miette::{miette, Result, Severity};
 
-use crate::config::CommitType as ConfigCommitType;
 #[cfg(feature = "gh_cli")]
 use crate::gh_cli;
+use crate::{
+    config::{CommitType as ConfigCommitType, Scope as ConfigScope},
+    suggest::{did_you_mean, is_fuzzy_match},
+};
 
 #[derive(Debug, Clone)]
 pub enum Prompts {
@@ -43,6 +47,7 @@ fn to_miette(err: InquireError) -> miette::Report {
 
 pub fn commit_type(
     commit_types: HashMap<String, ConfigCommitType>,
+    default: Option<&CommitType>,
 ) -> Result<CommitType, miette::Report> {
     let mut filtered_commit_types: Vec<CommitType> = vec![];
     let commit_types: Vec<ConfigCommitType> = commit_types.values().cloned().collect();
@@ -63,19 +68,93 @@ pub fn commit_type(
             .unwrap_or(usize::MAX)
     });
 
+    let cursor = default
+        .and_then(|default| {
+            filtered_commit_types
+                .iter()
+                .position(|c| c.as_str() == default.as_str())
+        })
+        .unwrap_or(0);
+
     Select::new(
         "Select the type of change that you're committing",
         filtered_commit_types,
     )
+    .with_starting_cursor(cursor)
     .prompt()
     .map_err(to_miette)
 }
 
-pub fn scope() -> Result<Option<Scope>, miette::Report> {
+/// An option of the scope picker.
+#[derive(Debug, Clone)]
+enum ScopeChoice {
+    Catalogue(ConfigScope),
+    Custom,
+    Skip,
+}
+impl fmt::Display for ScopeChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScopeChoice::Catalogue(scope) => write!(f, "{} - {}", scope.name, scope.description),
+            ScopeChoice::Custom => write!(f, "<custom>"),
+            ScopeChoice::Skip => write!(f, "<none>"),
+        }
+    }
+}
+
+/// Prompts for a scope, picking from the `[[scopes]]` catalogue when one is configured.
+///
+/// Free text is only offered without a catalogue, or when `allow_custom` is set. `suggested`,
+/// typically inferred from the staged changes, is pre-selected.
+pub fn scope(
+    catalogue: &[ConfigScope],
+    allow_custom: bool,
+    known: &[String],
+    suggested: Option<&str>,
+) -> Result<Option<Scope>, miette::Report> {
+    if catalogue.is_empty() {
+        return free_text_scope(known, suggested);
+    }
+
+    let mut choices: Vec<ScopeChoice> = catalogue
+        .iter()
+        .cloned()
+        .map(ScopeChoice::Catalogue)
+        .collect();
+
+    if allow_custom {
+        choices.push(ScopeChoice::Custom);
+    }
+    choices.push(ScopeChoice::Skip);
+
+    let cursor = suggested
+        .and_then(|suggested| catalogue.iter().position(|scope| scope.name == suggested))
+        .unwrap_or(0);
+
+    let choice = Select::new("scope:", choices)
+        .with_filter(&|input, _, value, _| is_fuzzy_match(input, value))
+        .with_starting_cursor(cursor)
+        .prompt()
+        .map_err(to_miette)?;
+
+    match choice {
+        ScopeChoice::Catalogue(scope) => parse_scope(&scope.name)
+            .map(Some)
+            .map_err(|error| miette::Error::new(error.inner.into_miette())),
+        ScopeChoice::Custom => free_text_scope(known, suggested),
+        ScopeChoice::Skip => Ok(None),
+    }
+}
+
+fn free_text_scope(
+    known: &[String],
+    suggested: Option<&str>,
+) -> Result<Option<Scope>, miette::Report> {
     let mut scope: Option<Scope> = None;
 
     while let Some(choice) = Text::new("scope:")
         .with_help_message("a noun description")
+        .with_initial_value(suggested.unwrap_or_default())
         .prompt_skippable()
         .map_err(to_miette)?
     {
@@ -86,6 +165,30 @@ pub fn scope() -> Result<Option<Scope>, miette::Report> {
 
         match parse_scope(&choice) {
             Ok(answer) => {
+                // a near miss of a known scope is likely a typo, so offer another try
+                if !known.contains(&answer.noun) {
+                    if let Some(suggestion) =
+                        did_you_mean(&answer.noun, known.iter().map(String::as_str))
+                    {
+                        let warning = miette!(
+                            severity = Severity::Warning,
+                            help = format!("did you mean `{}`?", suggestion),
+                            "Unknown scope {:?}",
+                            answer.noun
+                        );
+                        eprintln!("{:?}", warning);
+
+                        let keep = Confirm::new(&format!("use {:?} anyway?", answer.noun))
+                            .with_default(false)
+                            .prompt()
+                            .map_err(to_miette)?;
+
+                        if !keep {
+                            continue;
+                        }
+                    }
+                }
+
                 scope = Some(answer);
                 // Break after successfully parsing a scope
                 break;
@@ -101,16 +204,18 @@ pub fn scope() -> Result<Option<Scope>, miette::Report> {
     Ok(scope)
 }
 
-pub fn subject() -> Result<String, miette::Report> {
+pub fn subject(default: Option<&str>) -> Result<String, miette::Report> {
     Text::new("subject:")
         .with_validator(required!("subject is required"))
+        .with_initial_value(default.unwrap_or_default())
         .prompt()
         .map_err(to_miette)
 }
 
-pub fn body() -> Result<String, miette::Report> {
+pub fn body(default: Option<&str>) -> Result<String, miette::Report> {
     Text::new("body: ")
         .with_help_message("contextual information about the code changes")
+        .with_initial_value(default.unwrap_or_default())
         .with_formatter(&|submission| {
             if submission.is_empty() {
                 String::from("<skipped>")
@@ -122,9 +227,9 @@ pub fn body() -> Result<String, miette::Report> {
         .map_err(to_miette)
 }
 
-pub fn breaking_change() -> Result<bool, miette::Report> {
+pub fn breaking_change(default: bool) -> Result<bool, miette::Report> {
     Confirm::new("is breaking change:")
-        .with_default(false)
+        .with_default(default)
         .prompt()
         .map_err(to_miette)
 }
@@ -139,10 +244,16 @@ pub fn issues() -> Result<Vec<Footer>, miette::Report> {
     }
 }
 
-pub fn footers() -> Result<Option<Vec<Footer>>, miette::Report> {
+pub fn footers(default: &[Footer]) -> Result<Option<Vec<Footer>>, miette::Report> {
     let mut footers: Option<Vec<Footer>> = None;
+    let predefined: Vec<String> = default
+        .iter()
+        .map(|footer| format!("{}{}{}", footer.token, footer.separator, footer.content))
+        .collect();
+    let predefined = predefined.join("\n");
 
     while let Some(ans) = Editor::new("footer:")
+        .with_predefined_text(&predefined)
         .with_formatter(&|submission| {
             if submission.is_empty() {
                 String::from("<skipped>")
@@ -173,6 +284,58 @@ pub fn footers() -> Result<Option<Vec<Footer>>, miette::Report> {
     Ok(footers)
 }
 
+/// A commit offered by [`pick_commit`], shown as its short id and summary.
+struct CommitChoice {
+    oid: Oid,
+    summary: String,
+}
+impl fmt::Display for CommitChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let oid = self.oid.to_string();
+
+        write!(f, "{} {}", &oid[..7].yellow(), self.summary)
+    }
+}
+
+/// Asks which of `commits` (id and message pairs) to target, e.g. for `commit --fixup`.
+pub fn pick_commit(message: &str, commits: Vec<(Oid, String)>) -> Result<Oid, miette::Report> {
+    let choices: Vec<CommitChoice> = commits
+        .into_iter()
+        .map(|(oid, message)| CommitChoice {
+            oid,
+            summary: message.lines().next().unwrap_or_default().to_string(),
+        })
+        .collect();
+
+    Select::new(message, choices)
+        .with_page_size(10)
+        .prompt()
+        .map(|choice| choice.oid)
+        .map_err(to_miette)
+}
+
+/// Offers to stage the changes to `paths`, when nothing is staged yet.
+pub fn stage_tracked(paths: &[PathBuf]) -> Result<bool, miette::Report> {
+    let mut listed: Vec<String> = paths
+        .iter()
+        .take(5)
+        .map(|path| path.display().to_string())
+        .collect();
+
+    if paths.len() > listed.len() {
+        listed.push(format!("and {} more", paths.len() - listed.len()));
+    }
+
+    Confirm::new(&format!(
+        "Nothing is staged, stage the changes to {} tracked file(s)?",
+        paths.len()
+    ))
+    .with_help_message(&listed.join(", "))
+    .with_default(true)
+    .prompt()
+    .map_err(to_miette)
+}
+
 pub fn confirm_commit(mut commit: ConventionalCommit) -> InquireResult<bool> {
     let fancy_prompt = format!(
         "{} {}\n \n{}\n\n{} {}\n",
@@ -0,0 +1,356 @@
+use crate::lint::{self, Severity, Violation};
+use cc_scanner::errors::ParseError;
+use serde::Serialize;
+use serde_json::json;
+
+/// Output formats supported by the checking subcommands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Sarif,
+    Junit,
+}
+impl From<&str> for Format {
+    fn from(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Self::Json,
+            "sarif" => Self::Sarif,
+            "junit" => Self::Junit,
+            _ => Self::Text,
+        }
+    }
+}
+impl Format {
+    pub fn variants() -> [&'static str; 4] {
+        ["text", "json", "sarif", "junit"]
+    }
+}
+
+/// Why a commit message failed to check, flattened for machine-readable output.
+#[derive(Debug, Clone, Serialize)]
+pub struct Failure {
+    /// The `ParseErrorKind` variant name, e.g. `InvalidCommitType`.
+    pub kind: String,
+    pub description: String,
+    pub detail: String,
+    pub line: usize,
+    pub column: usize,
+}
+impl From<&ParseError> for Failure {
+    fn from(error: &ParseError) -> Self {
+        let (line, column) = error.line_col();
+
+        Failure {
+            kind: format!("{:?}", error.kind),
+            description: error.kind.to_string(),
+            detail: error.detail(),
+            line,
+            column,
+        }
+    }
+}
+
+/// A lint rule violation, flattened for machine-readable output.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleViolation {
+    pub rule: String,
+    pub severity: String,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+impl RuleViolation {
+    pub fn new(message: &str, violation: &Violation) -> Self {
+        let offset = violation.span.as_ref().map_or(0, |span| span.start);
+        let (line, column) = lint::line_col(message, offset);
+
+        RuleViolation {
+            rule: violation.rule.to_string(),
+            severity: violation.severity.to_string(),
+            message: violation.message.clone(),
+            line,
+            column,
+        }
+    }
+
+    fn is_error(&self) -> bool {
+        self.severity == Severity::Error.to_string()
+    }
+}
+
+/// The result of checking a single commit message.
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    pub sha: Option<String>,
+    pub message: String,
+    pub valid: bool,
+    /// Why the message failed to parse, if it did.
+    pub failure: Option<Failure>,
+    pub violations: Vec<RuleViolation>,
+}
+impl Record {
+    /// A short name for the checked message: its SHA if known, otherwise its first line.
+    fn name(&self) -> String {
+        match &self.sha {
+            Some(sha) => sha.clone(),
+            None => self.message.lines().next().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+pub fn to_json(records: &[Record]) -> String {
+    serde_json::to_string_pretty(records).expect("records are always serializable")
+}
+
+/// Renders records as a SARIF 2.1.0 log, as consumed by GitHub code scanning.
+pub fn to_sarif(records: &[Record]) -> String {
+    let mut rules: Vec<serde_json::Value> = vec![];
+    let mut results: Vec<serde_json::Value> = vec![];
+
+    for record in records {
+        let mut findings: Vec<(&str, &str, &str, String, usize, usize)> = vec![];
+
+        if let Some(failure) = &record.failure {
+            findings.push((
+                &failure.kind,
+                &failure.description,
+                "error",
+                format!("{}: {}", failure.description, failure.detail),
+                failure.line,
+                failure.column,
+            ));
+        }
+
+        for violation in &record.violations {
+            findings.push((
+                &violation.rule,
+                &violation.message,
+                if violation.is_error() {
+                    "error"
+                } else {
+                    "warning"
+                },
+                violation.message.clone(),
+                violation.line,
+                violation.column,
+            ));
+        }
+
+        for (rule_id, description, level, text, line, column) in findings {
+            if !rules.iter().any(|rule| rule["id"] == rule_id) {
+                rules.push(json!({
+                    "id": rule_id,
+                    "shortDescription": { "text": description },
+                }));
+            }
+
+            results.push(json!({
+                "ruleId": rule_id,
+                "level": level,
+                "message": { "text": text },
+                "locations": [{
+                    "physicalLocation": {
+                        // the message is not a file of the repository, so results point to the
+                        // file git edits messages in; the commit itself is a logical location
+                        "artifactLocation": {
+                            "uri": ".git/COMMIT_EDITMSG",
+                            "uriBaseId": "%SRCROOT%",
+                        },
+                        "region": {
+                            "startLine": line,
+                            "startColumn": column,
+                            "snippet": { "text": record.message },
+                        },
+                    },
+                    "logicalLocations": [{
+                        "kind": "commit",
+                        "fullyQualifiedName": record.name(),
+                    }],
+                }],
+                "properties": {
+                    "sha": record.sha,
+                    "message": record.message,
+                },
+            }));
+        }
+    }
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/lalilul3lo/baouncer",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+
+    serde_json::to_string_pretty(&log).expect("SARIF log is always serializable")
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            other => escaped.push(other),
+        }
+    }
+
+    escaped
+}
+
+/// Renders records as a JUnit XML report with one test case per commit message.
+pub fn to_junit(records: &[Record]) -> String {
+    let failures = records.iter().filter(|record| !record.valid).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        env!("CARGO_PKG_NAME"),
+        records.len(),
+        failures
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"conventional commits\" tests=\"{}\" failures=\"{}\">\n",
+        records.len(),
+        failures
+    ));
+
+    for record in records {
+        let name = escape_xml(&record.name());
+
+        let errors: Vec<&RuleViolation> = record
+            .violations
+            .iter()
+            .filter(|violation| violation.is_error())
+            .collect();
+
+        let failure = match &record.failure {
+            Some(failure) => Some((
+                failure.kind.clone(),
+                failure.description.clone(),
+                format!("{}:{} {}", failure.line, failure.column, failure.detail),
+            )),
+            None if !errors.is_empty() => Some((
+                "RuleViolation".to_string(),
+                format!("{} rule violation(s)", errors.len()),
+                errors
+                    .iter()
+                    .map(|violation| {
+                        format!(
+                            "{}:{} {}: {}",
+                            violation.line, violation.column, violation.rule, violation.message
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )),
+            None => None,
+        };
+
+        match failure {
+            Some((kind, description, detail)) => {
+                let body = format!("{}\n\n{}", detail, record.message);
+
+                xml.push_str(&format!(
+                    "    <testcase classname=\"baouncer.check\" name=\"{}\">\n",
+                    name
+                ));
+                xml.push_str(&format!(
+                    "      <failure type=\"{}\" message=\"{}\">{}</failure>\n",
+                    escape_xml(&kind),
+                    escape_xml(&description),
+                    escape_xml(&body)
+                ));
+                xml.push_str("    </testcase>\n");
+            }
+            None => {
+                xml.push_str(&format!(
+                    "    <testcase classname=\"baouncer.check\" name=\"{}\"/>\n",
+                    name
+                ));
+            }
+        }
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cc_scanner::parse_commit;
+
+    fn records() -> Vec<Record> {
+        let message = "feat:missing <space> & more".to_string();
+        let error = parse_commit(&message).unwrap_err();
+
+        vec![
+            Record {
+                sha: Some("abc123".to_string()),
+                message: "fix: all good".to_string(),
+                valid: true,
+                failure: None,
+                violations: vec![],
+            },
+            Record {
+                sha: Some("def456".to_string()),
+                message,
+                valid: false,
+                failure: Some(Failure::from(&error)),
+                violations: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn when_rendering_json_it_should_include_the_error_kind_and_location() {
+        let value: serde_json::Value = serde_json::from_str(&to_json(&records())).unwrap();
+
+        assert_eq!(value[1]["failure"]["kind"], "InvalidTokenSeparator");
+        assert_eq!(value[1]["failure"]["line"], 1);
+        assert_eq!(value[1]["failure"]["column"], 5);
+        assert_eq!(value[1]["sha"], "def456");
+    }
+
+    #[test]
+    fn when_rendering_sarif_it_should_only_report_failures() {
+        let value: serde_json::Value = serde_json::from_str(&to_sarif(&records())).unwrap();
+        let results = value["runs"][0]["results"].as_array().unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "InvalidTokenSeparator");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"]["startColumn"],
+            5
+        );
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            ".git/COMMIT_EDITMSG"
+        );
+    }
+
+    #[test]
+    fn when_rendering_junit_it_should_escape_messages() {
+        let xml = to_junit(&records());
+
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<failure type=\"InvalidTokenSeparator\""));
+        assert!(xml.contains("feat:missing &lt;space&gt; &amp; more"));
+    }
+}
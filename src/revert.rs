@@ -0,0 +1,163 @@
+use cc_scanner::{
+    conventional_commit::{CommitType, ConventionalCommit, Footer, Separator},
+    parse_commit,
+};
+use git2::{build::CheckoutBuilder, Commit, Oid, Repository};
+use std::{fs, io, path::PathBuf};
+use thiserror::Error;
+
+use crate::check::cleanup_message;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RevertError {
+    /// Reverting a merge needs to know which parent to revert to.
+    #[error("Commit {oid} is a merge, reverting merges is not supported")]
+    Merge { oid: Oid },
+
+    #[error("Changes are staged, commit or unstage them before reverting")]
+    StagedChanges,
+
+    /// The conflicted revert is left in place, with the conventional message ready for `git
+    /// commit`.
+    #[error("Reverting {oid} conflicts, resolve the conflicts and run `git commit` to finish")]
+    Conflicts { oid: Oid },
+
+    #[error("I/O error writing {path:?}: {source}")]
+    IoError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Git error: {0}")]
+    Git(#[from] git2::Error),
+}
+
+/// Builds the conventional commit reverting `target`.
+///
+/// The description repeats the header of `target` and a `Refs` footer points to it. Reverting a
+/// breaking change is itself breaking, so the breaking change indicator is carried over.
+pub fn message(target: &Commit) -> ConventionalCommit {
+    let original = String::from_utf8_lossy(target.message_bytes());
+    let is_breaking_change = parse_commit(&cleanup_message(&original)).is_ok_and(|commit| {
+        commit.is_breaking_change || commit.footers.iter().any(Footer::is_breaking_change)
+    });
+
+    ConventionalCommit {
+        commit_type: CommitType::Revert,
+        scope: None,
+        description: target.summary().unwrap_or_default().trim().to_string(),
+        body: None,
+        footers: vec![Footer {
+            token: "Refs".to_string(),
+            separator: Separator::Colon,
+            content: target.id().to_string(),
+        }],
+        is_breaking_change,
+    }
+}
+
+/// Applies the inverse of `target` to the index and working tree, ready to be committed with
+/// `message`.
+///
+/// On conflicts, `message` is left in `MERGE_MSG` for `git commit` to pick up once they are
+/// resolved, as `git revert` does with its own message.
+pub fn apply(repo: &Repository, target: &Commit, message: &str) -> Result<(), RevertError> {
+    if target.parent_count() > 1 {
+        return Err(RevertError::Merge { oid: target.id() });
+    }
+
+    let head_tree = repo.head()?.peel_to_tree()?;
+    let staged = repo.diff_tree_to_index(Some(&head_tree), None, None)?;
+
+    if staged.deltas().len() > 0 {
+        return Err(RevertError::StagedChanges);
+    }
+
+    repo.revert(target, None)?;
+
+    if repo.index()?.has_conflicts() {
+        let path = repo.path().join("MERGE_MSG");
+
+        fs::write(&path, format!("{}\n", message))
+            .map_err(|source| RevertError::IoError { path, source })?;
+
+        return Err(RevertError::Conflicts { oid: target.id() });
+    }
+
+    Ok(())
+}
+
+/// Undoes a successful `apply` whose commit failed, restoring the files it changed to HEAD and
+/// leaving the revert state. Unrelated changes in the working tree are kept.
+pub fn abort(repo: &Repository) -> Result<(), RevertError> {
+    let head_tree = repo.head()?.peel_to_tree()?;
+    let reverted = repo.diff_tree_to_index(Some(&head_tree), None, None)?;
+    let mut checkout = CheckoutBuilder::new();
+
+    for delta in reverted.deltas() {
+        if let Some(path) = delta.new_file().path().or(delta.old_file().path()) {
+            checkout.path(path);
+        }
+    }
+
+    if reverted.deltas().len() > 0 {
+        repo.checkout_tree(head_tree.as_object(), Some(checkout.force()))?;
+    }
+
+    repo.cleanup_state()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit_file, init_repo};
+    use std::path::Path;
+
+    #[test]
+    fn when_reverting_a_breaking_change_it_should_stay_breaking() {
+        let (dir, repo) = init_repo("revert");
+
+        commit_file(&repo, "a", "feat: add a");
+        let target = commit_file(&repo, "b", "feat(cli)!: add b");
+        let target = repo.find_commit(target).unwrap();
+
+        let mut commit = message(&target);
+
+        assert_eq!(
+            commit.as_str(),
+            format!("revert!: feat(cli)!: add b\n\nRefs: {}", target.id())
+        );
+        assert!(parse_commit(&commit.as_str()).is_ok());
+
+        apply(&repo, &target, &commit.as_str()).unwrap();
+
+        assert!(!dir.join("b").exists());
+        assert!(repo.index().unwrap().get_path(Path::new("b"), 0).is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_aborting_a_revert_it_should_only_restore_the_reverted_files() {
+        let (dir, repo) = init_repo("revert-abort");
+
+        commit_file(&repo, "a", "feat: add a");
+        let target = commit_file(&repo, "b", "feat: add b");
+        let target = repo.find_commit(target).unwrap();
+        fs::write(dir.join("a"), "unstaged").unwrap();
+
+        apply(&repo, &target, "revert: feat: add b").unwrap();
+        abort(&repo).unwrap();
+
+        assert!(dir.join("b").exists());
+        assert!(repo.index().unwrap().get_path(Path::new("b"), 0).is_some());
+        assert_eq!(fs::read_to_string(dir.join("a")).unwrap(), "unstaged");
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
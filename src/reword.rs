@@ -0,0 +1,162 @@
+use crate::git::{self, CommitError};
+use git2::{Commit, Oid, Repository};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RewordError {
+    /// History between the commit and HEAD is not linear, so it cannot simply be replayed.
+    #[error("Commit {oid} is a merge, reword cannot replay history across merges")]
+    MergeInTheWay { oid: Oid },
+
+    #[error("Commit {oid} is not an ancestor of HEAD")]
+    NotAnAncestor { oid: Oid },
+
+    #[error("{0}")]
+    Commit(#[from] CommitError),
+
+    #[error("Git error: {0}")]
+    Git(#[from] git2::Error),
+}
+
+/// Returns the commits from `target` up to HEAD, oldest first.
+///
+/// Fails if any of them, `target` included, is a merge, as replaying a merge would need to
+/// recreate its conflict resolution.
+pub fn descendants<'r>(repo: &'r Repository, target: Oid) -> Result<Vec<Commit<'r>>, RewordError> {
+    let mut commits = vec![];
+    let mut current = repo.head()?.peel_to_commit()?;
+
+    loop {
+        if current.parent_count() > 1 {
+            return Err(RewordError::MergeInTheWay { oid: current.id() });
+        }
+
+        let done = current.id() == target;
+        let parent = current.parent(0).ok();
+
+        commits.push(current);
+
+        if done {
+            break;
+        }
+
+        current = parent.ok_or(RewordError::NotAnAncestor { oid: target })?;
+    }
+
+    commits.reverse();
+
+    Ok(commits)
+}
+
+/// Replaces the message of `target` and replays the commits after it onto the result.
+///
+/// Authors, trees and the other messages are kept; the committer becomes the current user,
+/// as with `git rebase`. The current branch (or detached HEAD) is moved to the new tip, whose
+/// tree is unchanged, so the index and working tree are left alone. The replayed commits are
+/// signed as new commits would be, see [`git::create_commit`]. Returns the new tip.
+pub fn reword(
+    repo: &Repository,
+    target: Oid,
+    message: &str,
+    sign: Option<bool>,
+) -> Result<Oid, RewordError> {
+    let commits = descendants(repo, target)?;
+    let committer = repo.signature()?;
+    let mut parent: Option<Commit> = commits[0].parent(0).ok();
+
+    for (index, commit) in commits.iter().enumerate() {
+        let original = String::from_utf8_lossy(commit.message_bytes());
+        let message = if index == 0 { message } else { &original };
+        let parents: Vec<&Commit> = parent.iter().collect();
+
+        let oid = git::create_commit(
+            repo,
+            &commit.author(),
+            &committer,
+            message,
+            &commit.tree()?,
+            &parents,
+            sign,
+        )?;
+
+        parent = Some(repo.find_commit(oid)?);
+    }
+
+    let tip = parent.expect("at least the target commit is replayed").id();
+    let mut head = repo.head()?;
+    let log_message = format!("baouncer: reword {}", target);
+
+    if head.is_branch() {
+        // moves the branch itself, whatever its name is encoded in
+        head.set_target(tip, &log_message)?;
+    } else {
+        repo.set_head_detached(tip)?;
+    }
+
+    Ok(tip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit_file, init_repo};
+    use std::fs;
+
+    #[test]
+    fn when_rewording_a_commit_it_should_replay_its_descendants() {
+        let (dir, repo) = init_repo("reword");
+
+        commit_file(&repo, "a", "feat: a");
+        let target = commit_file(&repo, "b", "added b");
+        let tip = commit_file(&repo, "c", "fix: c");
+
+        let new_tip = reword(&repo, target, "feat: add b", None).unwrap();
+        let messages: Vec<String> = crate::git::commits_in_range(&repo, "HEAD", false)
+            .unwrap()
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect();
+
+        assert_eq!(messages, vec!["feat: a", "feat: add b", "fix: c"]);
+        assert_eq!(repo.head().unwrap().target(), Some(new_tip));
+
+        let new_tip = repo.find_commit(new_tip).unwrap();
+        let old_tip = repo.find_commit(tip).unwrap();
+
+        assert_eq!(new_tip.tree_id(), old_tip.tree_id());
+        assert_eq!(new_tip.author().name(), Some("author"));
+        assert_eq!(new_tip.committer().name(), Some("committer"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn when_a_merge_is_in_the_way_it_should_refuse() {
+        let (dir, repo) = init_repo("reword-merge");
+
+        let base = commit_file(&repo, "a", "feat: a");
+        let target = commit_file(&repo, "b", "added b");
+
+        let sig = repo.signature().unwrap();
+        let base = repo.find_commit(base).unwrap();
+        let head = repo.find_commit(target).unwrap();
+        let merge = repo
+            .commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                "Merge",
+                &head.tree().unwrap(),
+                &[&head, &base],
+            )
+            .unwrap();
+
+        assert!(matches!(
+            reword(&repo, target, "feat: add b", None),
+            Err(RewordError::MergeInTheWay { oid }) if oid == merge
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+use git2::Config as GitConfig;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SignError {
+    #[error("Signing with SSH requires user.signingkey to be set")]
+    NoSigningKey,
+
+    #[error("Unsupported gpg.format {format:?}, expected openpgp, x509 or ssh")]
+    UnknownFormat { format: String },
+
+    #[error("Failed to run {program:?}: {source}")]
+    Program {
+        program: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("{program:?} failed to sign the commit: {stderr}")]
+    Failed { program: String, stderr: String },
+
+    #[error("Git error: {0}")]
+    Git(#[from] git2::Error),
+}
+
+/// The kinds of signature `git` can produce, set through `gpg.format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureFormat {
+    OpenPgp,
+    X509,
+    Ssh,
+}
+impl SignatureFormat {
+    /// The program `git` signs with for this format unless `gpg.<format>.program` says otherwise.
+    fn default_program(&self) -> &'static str {
+        match self {
+            SignatureFormat::OpenPgp => "gpg",
+            SignatureFormat::X509 => "gpgsm",
+            SignatureFormat::Ssh => "ssh-keygen",
+        }
+    }
+}
+
+/// Signs commits the way `git` would with the same configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signer {
+    pub format: SignatureFormat,
+    pub program: String,
+    /// `user.signingkey`, falling back to the committer identity for OpenPGP and X.509.
+    pub key: String,
+}
+impl Signer {
+    /// Reads `gpg.format`, the program for that format and `user.signingkey` from `config`.
+    pub fn from_config(config: &GitConfig) -> Result<Self, SignError> {
+        let format = match config.get_string("gpg.format").ok().as_deref() {
+            None | Some("openpgp") => SignatureFormat::OpenPgp,
+            Some("x509") => SignatureFormat::X509,
+            Some("ssh") => SignatureFormat::Ssh,
+            Some(format) => {
+                return Err(SignError::UnknownFormat {
+                    format: format.to_string(),
+                })
+            }
+        };
+
+        let program = match format {
+            // `gpg.program` predates the other formats and still applies to OpenPGP
+            SignatureFormat::OpenPgp => config
+                .get_string("gpg.openpgp.program")
+                .or_else(|_| config.get_string("gpg.program")),
+            SignatureFormat::X509 => config.get_string("gpg.x509.program"),
+            SignatureFormat::Ssh => config.get_string("gpg.ssh.program"),
+        }
+        .unwrap_or_else(|_| format.default_program().to_string());
+
+        let key = match config.get_string("user.signingkey") {
+            Ok(key) => key,
+            Err(_) if format == SignatureFormat::Ssh => return Err(SignError::NoSigningKey),
+            Err(_) => format!(
+                "{} <{}>",
+                config.get_string("user.name")?,
+                config.get_string("user.email")?
+            ),
+        };
+
+        Ok(Signer {
+            format,
+            program,
+            key,
+        })
+    }
+
+    /// Returns the detached signature of `content`, ready for `Repository::commit_signed`.
+    pub fn sign(&self, content: &str) -> Result<String, SignError> {
+        let mut command = Command::new(&self.program);
+        // a literal public key has to be handed to ssh-keygen as a file
+        let mut literal_key: Option<PathBuf> = None;
+
+        match self.format {
+            SignatureFormat::OpenPgp | SignatureFormat::X509 => {
+                command.args(["--status-fd=2", "-bsau", &self.key]);
+            }
+            SignatureFormat::Ssh => {
+                command.args(["-Y", "sign", "-n", "git"]);
+
+                if let Some(public_key) = self.key.strip_prefix("key::") {
+                    let path = write_literal_key(public_key)
+                        .map_err(|source| self.program_error(source))?;
+
+                    // the private key is only available through the agent
+                    command.arg("-U").arg("-f").arg(&path);
+                    literal_key = Some(path);
+                } else {
+                    command.arg("-f").arg(expand_home(&self.key));
+                }
+            }
+        }
+
+        let output = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                child
+                    .stdin
+                    .take()
+                    .expect("stdin is piped")
+                    .write_all(content.as_bytes())?;
+                child.wait_with_output()
+            });
+
+        if let Some(path) = literal_key {
+            let _ = fs::remove_file(path);
+        }
+
+        let output = output.map_err(|source| self.program_error(source))?;
+        let signature = String::from_utf8_lossy(&output.stdout).to_string();
+
+        if !output.status.success() || signature.trim().is_empty() {
+            return Err(SignError::Failed {
+                program: self.program.clone(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        Ok(signature)
+    }
+
+    fn program_error(&self, source: io::Error) -> SignError {
+        SignError::Program {
+            program: self.program.clone(),
+            source,
+        }
+    }
+}
+
+/// Writes a literal public key to a new file of the temp directory. The directory may be shared,
+/// so an existing path is never reused, nor followed if it is a symlink.
+fn write_literal_key(public_key: &str) -> io::Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos());
+
+    for attempt in 0..16 {
+        let path = std::env::temp_dir().join(format!(
+            "baouncer-signingkey-{}-{}-{}.pub",
+            std::process::id(),
+            nanos,
+            attempt
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = match options.open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        };
+
+        if let Err(err) = file.write_all(public_key.as_bytes()) {
+            let _ = fs::remove_file(&path);
+
+            return Err(err);
+        }
+
+        return Ok(path);
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "no free path for the signing key in the temp directory",
+    ))
+}
+
+/// Expands a leading `~/`, as `git` does for `user.signingkey` paths.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Whether new commits should be signed: `--sign`/`--no-sign` when given, `commit.gpgsign`
+/// otherwise.
+pub fn should_sign(config: &GitConfig, sign: Option<bool>) -> bool {
+    sign.unwrap_or_else(|| config.get_bool("commit.gpgsign").unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str, entries: &[(&str, &str)]) -> (PathBuf, GitConfig) {
+        let path = std::env::temp_dir().join(format!(
+            "baouncer-sign-{}-{}.gitconfig",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let mut config = GitConfig::open(&path).unwrap();
+
+        for (key, value) in entries {
+            config.set_str(key, value).unwrap();
+        }
+
+        (path, config)
+    }
+
+    #[test]
+    fn when_no_format_is_set_it_should_sign_with_gpg_as_the_committer() {
+        let (path, config) = config(
+            "openpgp",
+            &[("user.name", "a"), ("user.email", "a@example.com")],
+        );
+
+        assert_eq!(
+            Signer::from_config(&config).unwrap(),
+            Signer {
+                format: SignatureFormat::OpenPgp,
+                program: "gpg".to_string(),
+                key: "a <a@example.com>".to_string(),
+            }
+        );
+        assert!(!should_sign(&config, None));
+        assert!(should_sign(&config, Some(true)));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn when_the_format_is_ssh_it_should_require_a_signing_key() {
+        let (path, mut config) =
+            config("ssh", &[("gpg.format", "ssh"), ("commit.gpgsign", "true")]);
+
+        assert!(matches!(
+            Signer::from_config(&config),
+            Err(SignError::NoSigningKey)
+        ));
+        assert!(should_sign(&config, None));
+        assert!(!should_sign(&config, Some(false)));
+
+        config
+            .set_str("user.signingkey", "~/.ssh/id_ed25519")
+            .unwrap();
+        config
+            .set_str("gpg.ssh.program", "/opt/ssh-keygen")
+            .unwrap();
+
+        let signer = Signer::from_config(&config).unwrap();
+
+        assert_eq!(signer.format, SignatureFormat::Ssh);
+        assert_eq!(signer.program, "/opt/ssh-keygen");
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn when_writing_a_literal_key_it_should_create_a_new_file_each_time() {
+        let first = write_literal_key("ssh-ed25519 AAAA").unwrap();
+        let second = write_literal_key("ssh-ed25519 AAAA").unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(fs::read_to_string(&first).unwrap(), "ssh-ed25519 AAAA");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&first).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_file(first).unwrap();
+        fs::remove_file(second).unwrap();
+    }
+}
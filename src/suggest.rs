@@ -0,0 +1,91 @@
+/// Number of single-character insertions, deletions, substitutions and adjacent transpositions
+/// needed to turn `a` into `b` (optimal string alignment distance).
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            rows[i][j] = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                rows[i][j] = rows[i][j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Returns the candidate closest to `value`, if one is close enough to likely be what was meant.
+///
+/// Comparison ignores case, and a third of the length of `value` (at least one) in edits is
+/// tolerated. `value` itself is never suggested.
+pub fn did_you_mean<'a, I>(value: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let lower = value.to_lowercase();
+    let threshold = (lower.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != value)
+        .map(|candidate| (edit_distance(&lower, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Whether the characters of `pattern` appear in `candidate` in order, ignoring case.
+pub fn is_fuzzy_match(pattern: &str, candidate: &str) -> bool {
+    let mut candidate = candidate.chars().flat_map(char::to_lowercase);
+
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|c| candidate.any(|candidate| candidate == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_measuring_distance_it_should_count_transpositions_once() {
+        assert_eq!(edit_distance("fxi", "fix"), 1);
+        assert_eq!(edit_distance("refactr", "refactor"), 1);
+        assert_eq!(edit_distance("feat", "chore"), 5);
+    }
+
+    #[test]
+    fn when_a_candidate_is_close_it_should_be_suggested() {
+        let types = ["feat", "fix", "refactor", "docs"];
+
+        assert_eq!(did_you_mean("fxi", types), Some("fix"));
+        assert_eq!(did_you_mean("refactr", types), Some("refactor"));
+        assert_eq!(did_you_mean("Feat", types), Some("feat"));
+        assert_eq!(did_you_mean("feat", types), None);
+        assert_eq!(did_you_mean("release", types), None);
+    }
+
+    #[test]
+    fn when_fuzzy_matching_it_should_match_subsequences() {
+        assert!(is_fuzzy_match("ccsc", "cc-scanner - The parser"));
+        assert!(is_fuzzy_match("CLI", "cli - Command line"));
+        assert!(!is_fuzzy_match("xyz", "cli - Command line"));
+    }
+}
@@ -0,0 +1,58 @@
+//! Fixtures shared by the unit tests.
+use git2::{Commit, Oid, Repository, Signature};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// An empty directory in the system temp directory, unique to `name` and the test run.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("baouncer-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    dir
+}
+
+/// Initialises a repository in [`temp_dir`], with a committer identity so that commits can be
+/// created whatever the global configuration.
+pub fn init_repo(name: &str) -> (PathBuf, Repository) {
+    let dir = temp_dir(name);
+    let repo = Repository::init(&dir).unwrap();
+    let mut config = repo.config().unwrap();
+
+    config.set_str("user.name", "committer").unwrap();
+    config
+        .set_str("user.email", "committer@example.com")
+        .unwrap();
+
+    (dir, repo)
+}
+
+/// Writes `contents` to the file `name` of the working tree and stages it.
+pub fn stage(repo: &Repository, name: &str, contents: &str) {
+    fs::write(repo.workdir().unwrap().join(name), contents).unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(name)).unwrap();
+    index.write().unwrap();
+}
+
+/// Commits the index on top of HEAD as `author`, bypassing hooks and signing.
+pub fn commit(repo: &Repository, message: &str) -> Oid {
+    let sig = Signature::now("author", "author@example.com").unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    let parents: Vec<&Commit> = parent.iter().collect();
+
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .unwrap()
+}
+
+/// Stages the file `name`, holding `message`, and commits it with `message`.
+pub fn commit_file(repo: &Repository, name: &str, message: &str) -> Oid {
+    stage(repo, name, message);
+
+    commit(repo, message)
+}
@@ -0,0 +1,219 @@
+use log::debug;
+use semver::Version;
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    package: Option<Package>,
+    workspace: Option<Workspace>,
+}
+#[derive(Debug, Deserialize)]
+struct Package {
+    name: String,
+    version: Option<PackageVersion>,
+}
+/// `version = "1.2.3"`, or `version.workspace = true` to take `[workspace.package].version`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PackageVersion {
+    Literal(String),
+    Inherited { workspace: bool },
+}
+#[derive(Debug, Deserialize)]
+struct Workspace {
+    #[serde(default)]
+    members: Vec<String>,
+    package: Option<WorkspacePackage>,
+}
+#[derive(Debug, Deserialize)]
+struct WorkspacePackage {
+    version: Option<String>,
+}
+
+/// A crate of a Cargo workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    /// Directory of the crate relative to the workspace root, empty for the root package.
+    pub path: PathBuf,
+    /// The version in the crate's manifest, or the workspace's when the crate inherits it.
+    pub version: Option<Version>,
+}
+impl Member {
+    /// Whether `path`, relative to the workspace root, belongs to this crate.
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.path)
+    }
+}
+
+fn read_manifest(dir: &Path) -> Option<Manifest> {
+    let path = dir.join("Cargo.toml");
+    let contents = fs::read_to_string(&path).ok()?;
+
+    match toml::from_str(&contents) {
+        Ok(manifest) => Some(manifest),
+        Err(err) => {
+            debug!("ignoring unreadable manifest {:?}: {}", path, err);
+            None
+        }
+    }
+}
+
+fn member(root: &Path, path: PathBuf, workspace_version: Option<&str>) -> Option<Member> {
+    let package = read_manifest(&root.join(&path))?.package?;
+    let version = match &package.version {
+        Some(PackageVersion::Literal(version)) => Some(version.as_str()),
+        Some(PackageVersion::Inherited { workspace: true }) => workspace_version,
+        _ => None,
+    }
+    .and_then(|version| Version::parse(version).ok());
+
+    Some(Member {
+        name: package.name,
+        path,
+        version,
+    })
+}
+
+/// Lists the crates of the Cargo workspace rooted at `root`, most deeply nested first, so the
+/// first member containing a path is the crate it belongs to.
+///
+/// Member globs are supported in their common `dir/*` form. Returns an empty list when `root`
+/// is not a workspace.
+pub fn members(root: &Path) -> Vec<Member> {
+    let Some(workspace) = read_manifest(root).and_then(|manifest| manifest.workspace) else {
+        return vec![];
+    };
+
+    let workspace_version = workspace.package.and_then(|package| package.version);
+    let workspace_version = workspace_version.as_deref();
+    let mut members = vec![];
+
+    for pattern in workspace.members {
+        if let Some(parent) = pattern.strip_suffix("/*") {
+            let Ok(entries) = fs::read_dir(root.join(parent)) else {
+                continue;
+            };
+
+            for entry in entries.flatten() {
+                members.extend(member(
+                    root,
+                    Path::new(parent).join(entry.file_name()),
+                    workspace_version,
+                ));
+            }
+        } else {
+            let path: PathBuf = Path::new(&pattern)
+                .components()
+                .filter(|component| *component != Component::CurDir)
+                .collect();
+
+            members.extend(member(root, path, workspace_version));
+        }
+    }
+
+    members.sort_by_key(|member| std::cmp::Reverse(member.path.components().count()));
+
+    members
+}
+
+/// Finds the crate `path` belongs to.
+pub fn member_of<'a>(members: &'a [Member], path: &Path) -> Option<&'a Member> {
+    members.iter().find(|member| member.contains(path))
+}
+
+/// The crates touched by `paths`, in the order of `members`.
+pub fn attribute<'a>(members: &'a [Member], paths: &[PathBuf]) -> Vec<&'a Member> {
+    members
+        .iter()
+        .filter(|member| {
+            paths
+                .iter()
+                .any(|path| member_of(members, path) == Some(*member))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_reading_this_workspace_it_should_list_nested_crates_first() {
+        let members = members(Path::new(env!("CARGO_MANIFEST_DIR")));
+        let names: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+
+        assert_eq!(names, vec!["cc-scanner", "baouncer"]);
+        assert_eq!(members[0].path, PathBuf::from("cc-scanner"));
+        assert_eq!(members[1].path, PathBuf::new());
+        assert_eq!(
+            members[1].version,
+            Some(Version::parse(env!("CARGO_PKG_VERSION")).unwrap())
+        );
+
+        assert_eq!(
+            member_of(&members, Path::new("cc-scanner/src/lib.rs")).map(|m| m.name.as_str()),
+            Some("cc-scanner")
+        );
+        assert_eq!(
+            member_of(&members, Path::new("src/main.rs")).map(|m| m.name.as_str()),
+            Some("baouncer")
+        );
+    }
+
+    #[test]
+    fn when_attributing_paths_it_should_only_count_the_innermost_crate() {
+        let members = members(Path::new(env!("CARGO_MANIFEST_DIR")));
+        let paths = vec![
+            PathBuf::from("cc-scanner/src/lib.rs"),
+            PathBuf::from("cc-scanner/Cargo.toml"),
+        ];
+
+        let touched: Vec<&str> = attribute(&members, &paths)
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+
+        assert_eq!(touched, vec!["cc-scanner"]);
+    }
+
+    #[test]
+    fn when_a_crate_inherits_its_version_it_should_use_the_workspace_version() {
+        let root = crate::test_support::temp_dir("workspace");
+        fs::create_dir_all(root.join("crates/inherited")).unwrap();
+        fs::create_dir_all(root.join("crates/pinned")).unwrap();
+
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nversion = \"2.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("crates/inherited/Cargo.toml"),
+            "[package]\nname = \"inherited\"\nversion.workspace = true\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("crates/pinned/Cargo.toml"),
+            "[package]\nname = \"pinned\"\nversion = \"0.3.0\"\n",
+        )
+        .unwrap();
+
+        let members = members(&root);
+        let version = |name: &str| {
+            members
+                .iter()
+                .find(|member| member.name == name)
+                .and_then(|member| member.version.clone())
+        };
+
+        assert_eq!(version("inherited"), Some(Version::new(2, 1, 0)));
+        assert_eq!(version("pinned"), Some(Version::new(0, 3, 0)));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}